@@ -0,0 +1,303 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveTime, SecondsFormat};
+
+use crate::format;
+use crate::model::{Status, TodoFile};
+
+/// `## ログ` の1行（`- <時刻>: <メッセージ>`）を読んだもの
+/// - 時刻は RFC3339（秒まで）。以前の `YYYY-MM-DD` だけの行も読む
+/// - `done (status todo -> done)` の形なら action と from/to に分ける
+/// - `subtask add: x` / `repeat: next ...` のような `動作: 内容` は action と text に分ける
+/// - `clock in` のような動作名だけの行は action のみ
+/// - どれにも当てはまらなければ action は "note"
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub at: Option<Stamp>,
+    pub action: String,
+    pub from: Option<Status>,
+    pub to: Option<Status>,
+    pub text: String,
+}
+
+/// 日付だけの古い行と、時刻付きの行
+#[derive(Debug, Clone, Copy)]
+pub enum Stamp {
+    Date(NaiveDate),
+    At(DateTime<FixedOffset>),
+}
+
+impl Stamp {
+    fn parse(s: &str) -> Option<Stamp> {
+        if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+            return Some(Stamp::At(dt));
+        }
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").ok().map(Stamp::Date)
+    }
+
+    /// ローカルの日付
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            Stamp::Date(d) => *d,
+            Stamp::At(dt) => dt.with_timezone(&Local).date_naive(),
+        }
+    }
+
+    /// ローカルの時刻（日付だけの行は None）
+    pub fn time(&self) -> Option<NaiveTime> {
+        match self {
+            Stamp::Date(_) => None,
+            Stamp::At(dt) => Some(dt.with_timezone(&Local).time()),
+        }
+    }
+
+    /// 並べ替え用（同じ日なら日付だけの行を先に）
+    pub fn key(&self) -> (NaiveDate, Option<NaiveTime>) {
+        (self.date(), self.time())
+    }
+}
+
+/// 新しく書くログ行の時刻
+pub fn stamp() -> String {
+    Local::now().to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+/// 本文の `## ログ` 節を上から順に（ファイルでは新しいものが上）
+/// テンプレートの空欄（`- :`）は飛ばす
+pub fn parse(body: &str) -> Vec<Entry> {
+    let mut out = Vec::new();
+    let mut in_log = false;
+    for line in body.lines() {
+        if line.starts_with("## ") {
+            in_log = line.trim_end() == "## ログ";
+            continue;
+        }
+        if !in_log {
+            continue;
+        }
+        let Some(item) = line.trim().strip_prefix("- ").or_else(|| line.trim().strip_prefix('-')) else {
+            continue;
+        };
+        let item = item.trim();
+        if item.is_empty() || item == ":" {
+            continue;
+        }
+        out.push(parse_line(item));
+    }
+    out
+}
+
+/// `- ` を除いた1行
+fn parse_line(item: &str) -> Entry {
+    // RFC3339 の中に ": " は無いので、最初の ": " までが時刻
+    let (at, message) = match item.split_once(": ") {
+        Some((s, rest)) => match Stamp::parse(s.trim()) {
+            Some(at) => (Some(at), rest.trim()),
+            None => (None, item),
+        },
+        None => match item.strip_suffix(':').and_then(|s| Stamp::parse(s.trim())) {
+            Some(at) => (Some(at), ""),
+            None => (None, item),
+        },
+    };
+    let mut e = Entry { at, action: "note".to_string(), from: None, to: None, text: message.to_string() };
+
+    if let Some((action, rest)) = message.split_once(" (status ") {
+        if let Some((from, to)) = rest.trim_end_matches(')').split_once(" -> ") {
+            if let (Ok(from), Ok(to)) = (from.parse::<Status>(), to.parse::<Status>()) {
+                e.action = action.trim().to_string();
+                e.from = Some(from);
+                e.to = Some(to);
+                e.text.clear();
+                return e;
+            }
+        }
+    }
+    if let Some((action, rest)) = message.split_once(": ") {
+        // "subtask add" "repeat" "doctor" のような短い英小文字の動作名だけ
+        if action.len() <= 20 && action.chars().all(|c| c.is_ascii_lowercase() || c == ' ' || c == '-') {
+            e.action = action.to_string();
+            e.text = rest.to_string();
+            return e;
+        }
+    }
+    for (prefix, action) in [("imported from ", "import"), ("restored from ", "restore")] {
+        if let Some(rest) = message.strip_prefix(prefix) {
+            e.action = action.to_string();
+            e.text = format!("from {}", rest);
+            return e;
+        }
+    }
+    // "clock in" のように動作名だけの行
+    if !message.is_empty() && message.len() <= 20 && message.chars().all(|c| c.is_ascii_lowercase() || c == ' ') {
+        e.action = message.to_string();
+        e.text.clear();
+    }
+    e
+}
+
+/// action 以外の部分：`todo -> doing` / subtask の内容 など
+pub fn detail(e: &Entry) -> String {
+    match (&e.from, &e.to) {
+        (Some(from), Some(to)) if e.text.is_empty() => format!("{} -> {}", from.as_str(), to.as_str()),
+        (Some(from), Some(to)) => format!("{} -> {}  {}", from.as_str(), to.as_str(), e.text),
+        _ => e.text.clone(),
+    }
+}
+
+/// todo 1件分のログを古い順に（ファイルでは新しいものが上にある）
+pub fn entries(t: &TodoFile) -> Vec<Entry> {
+    let mut v = parse(&t.body);
+    v.reverse();
+    v
+}
+
+fn time_col(e: &Entry) -> String {
+    e.at.and_then(|a| a.time()).map(|t| t.format("%H:%M").to_string()).unwrap_or_default()
+}
+
+/// --action：完全一致か、"subtask" なら "subtask add" なども
+fn action_matches(e: &Entry, action: Option<&str>) -> bool {
+    let Some(a) = action else { return true; };
+    let a = a.trim().to_lowercase();
+    e.action == a || e.action.starts_with(&format!("{} ", a))
+}
+
+/// `todo log <id>`：1件のログを古い順に（since 以降の日付だけ）
+pub fn print_todo(t: &TodoFile, since: Option<NaiveDate>, action: Option<&str>) {
+    println!("{}  ({}, {})", t.fm.title, t.short_id(), t.fm.status.as_str());
+    let entries: Vec<Entry> = entries(t)
+        .into_iter()
+        .filter(|e| since.is_none_or(|s| e.at.is_some_and(|a| a.date() >= s)))
+        .filter(|e| action_matches(e, action))
+        .collect();
+    if entries.is_empty() {
+        println!("  (no log entries)");
+        return;
+    }
+    let w = entries.iter().map(|e| format::width(&e.action)).max().unwrap_or(0);
+    for e in &entries {
+        let date = e.at.map(|a| a.date().format("%Y-%m-%d").to_string()).unwrap_or_default();
+        let line = format!(
+            "{}  {}  {}  {}",
+            format::pad(&date, 10),
+            format::pad(&time_col(e), 5),
+            format::pad(&e.action, w),
+            detail(e)
+        );
+        println!("{}", line.trim_end());
+    }
+}
+
+/// `todo log`：全 todo のログを日ごとに古い順で（日付の読めない行は出さない）
+pub fn print_timeline(todos: &[TodoFile], since: NaiveDate, action: Option<&str>) {
+    let mut rows: Vec<(Stamp, &TodoFile, Entry)> = Vec::new();
+    for t in todos {
+        for e in entries(t).into_iter().filter(|e| action_matches(e, action)) {
+            if let Some(at) = e.at.filter(|a| a.date() >= since) {
+                rows.push((at, t, e));
+            }
+        }
+    }
+    if rows.is_empty() {
+        println!("no log entries since {}", since);
+        return;
+    }
+    // 同じ日の中は時刻順（日付だけの古い行が先）、同時刻ならファイル内の順
+    rows.sort_by_key(|(at, _, _)| at.key());
+
+    let w = rows.iter().map(|(_, _, e)| format::width(&e.action)).max().unwrap_or(0);
+    let mut day = None;
+    for (at, t, e) in &rows {
+        if day != Some(at.date()) {
+            if day.is_some() {
+                println!();
+            }
+            day = Some(at.date());
+            println!("{}", at.date().format("%Y-%m-%d %a"));
+        }
+        let d = detail(e);
+        let line = format!(
+            "  {}  {}  {}{}  {}",
+            format::pad(&time_col(e), 5),
+            format::pad(&e.action, w),
+            t.fm.title,
+            if d.is_empty() { String::new() } else { format!(": {}", d) },
+            t.short_id()
+        );
+        println!("{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(e: &Entry) -> String {
+        e.at.map(|a| a.date().format("%Y-%m-%d").to_string()).unwrap_or_default()
+    }
+
+    #[test]
+    fn reads_rfc3339_and_legacy_date_lines() {
+        let e = parse_line("2026-10-14T09:30:00+09:00: clock in");
+        assert!(matches!(e.at, Some(Stamp::At(_))));
+        assert!(e.at.unwrap().time().is_some());
+
+        let old = parse_line("2026-10-14: created");
+        assert!(matches!(old.at, Some(Stamp::Date(_))));
+        assert_eq!(date(&old), "2026-10-14");
+        assert_eq!(old.at.unwrap().time(), None);
+        assert_eq!(old.action, "created");
+
+        // 同じ日なら日付だけの行が先
+        assert!(old.at.unwrap().key() < Stamp::At(DateTime::parse_from_rfc3339("2026-10-14T12:00:00+00:00").unwrap()).key());
+    }
+
+    #[test]
+    fn status_changes_split_into_from_and_to() {
+        let e = parse_line("2026-01-05: start (status todo -> doing)");
+        assert_eq!(e.action, "start");
+        assert_eq!((e.from.clone(), e.to.clone()), (Some(Status::Todo), Some(Status::Doing)));
+        assert_eq!(detail(&e), "todo -> doing");
+
+        // status 名でなければただの文
+        let e = parse_line("2026-01-05: moved (status quo -> later)");
+        assert_eq!(e.action, "note");
+        assert_eq!(e.from, None);
+    }
+
+    #[test]
+    fn action_and_text() {
+        let e = parse_line("2026-01-05T10:00:00+09:00: subtask add: 見積もりを送る");
+        assert_eq!((e.action.as_str(), e.text.as_str()), ("subtask add", "見積もりを送る"));
+
+        let e = parse_line("2026-01-05: repeat: next 2026-02-05T10:00:00+09:00 (due 2026-02-05)");
+        assert_eq!(e.action, "repeat");
+        assert_eq!(e.text, "next 2026-02-05T10:00:00+09:00 (due 2026-02-05)");
+
+        let e = parse_line("2026-01-05: imported from todotxt (line 3)");
+        assert_eq!((e.action.as_str(), e.text.as_str()), ("import", "from todotxt (line 3)"));
+
+        // 大文字や日本語で始まる「動作: 」はメモ扱い
+        let e = parse_line("2026-01-05: 電話: 折り返し待ち");
+        assert_eq!((e.action.as_str(), e.text.as_str()), ("note", "電話: 折り返し待ち"));
+    }
+
+    #[test]
+    fn bare_actions_and_notes() {
+        let e = parse_line("2026-01-05T10:00:00+09:00: clock in");
+        assert_eq!((e.action.as_str(), e.text.as_str()), ("clock in", ""));
+
+        let e = parse_line("2026-01-05: Called the bank, waiting for reply");
+        assert_eq!((e.action.as_str(), e.text.as_str()), ("note", "Called the bank, waiting for reply"));
+
+        let e = parse_line("No timestamp here.");
+        assert!(e.at.is_none());
+        assert_eq!((e.action.as_str(), e.text.as_str()), ("note", "No timestamp here."));
+    }
+
+    #[test]
+    fn parse_reads_only_the_log_section() {
+        let body = "## メモ\n- 2026-01-01: not a log line\n\n## ログ\n- 2026-01-03T09:00:00+09:00: clock out\n- :\n- 2026-01-02: created\n\n## 次\n- 2026-01-04: ignored\n";
+        let got: Vec<(String, String)> = parse(body).iter().map(|e| (date(e), e.action.clone())).collect();
+        assert_eq!(got, [("2026-01-03".to_string(), "clock out".to_string()), ("2026-01-02".to_string(), "created".to_string())]);
+    }
+}
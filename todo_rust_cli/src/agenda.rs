@@ -0,0 +1,178 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime};
+
+use crate::config::Config;
+use crate::format::{self, Label};
+use crate::model::TodoFile;
+
+/// 期限の日付（ローカル）と、RFC3339 なら時刻
+fn due_day(t: &TodoFile) -> Option<(NaiveDate, Option<NaiveTime>)> {
+    let s = t.fm.due.as_deref()?;
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Some((d, None));
+    }
+    let dt = DateTime::parse_from_rfc3339(s).ok()?.with_timezone(&Local);
+    Some((dt.date_naive(), Some(dt.time())))
+}
+
+fn enable_color() -> bool {
+    std::env::var("NO_COLOR").is_err()
+}
+
+/// `todo agenda`：今日から days 日分を日ごとに
+/// - 先頭に OVERDUE、末尾に NO DUE（期限が読めないものも含む）
+/// - todos はフィルタ・並べ替え済みの active（日の中ではこの順）
+/// - active は BLOCKED 判定用の active/ 全件
+pub fn print_agenda(cfg: &Config, todos: &[TodoFile], active: &[TodoFile], days: i64) -> Result<()> {
+    if days < 1 {
+        return Err(anyhow!("--days must be at least 1"));
+    }
+    let color = enable_color();
+    let today = Local::now().date_naive();
+    let last = today + Duration::days(days - 1);
+    let label = |t: &TodoFile| format::label_for_in(t, active, cfg.soon_days);
+
+    let overdue: Vec<&TodoFile> = todos.iter().filter(|t| format::label_for(t, cfg.soon_days) == Label::Overdue).collect();
+    let no_due: Vec<&TodoFile> = todos.iter().filter(|t| due_day(t).is_none()).collect();
+
+    if !overdue.is_empty() {
+        println!("{}", format::paint(Label::Overdue, &format!("OVERDUE ({})", overdue.len()), color));
+        for t in &overdue {
+            let (d, time) = due_day(t).unwrap_or((today, None));
+            let when = match time {
+                Some(tm) => format!("{} {}", d.format("%m-%d"), tm.format("%H:%M")),
+                None => d.format("%m-%d").to_string(),
+            };
+            println!("{}", item_line(t, &format::paint(label(t), &format::pad(&when, 11), color)));
+        }
+        println!();
+    }
+
+    let mut day = today;
+    while day <= last {
+        let mark = if day == today { "  today" } else if day == today + Duration::days(1) { "  tomorrow" } else { "" };
+        println!("{}{}", day.format("%Y-%m-%d %a"), mark);
+
+        let items: Vec<&TodoFile> = todos
+            .iter()
+            .filter(|t| format::label_for(t, cfg.soon_days) != Label::Overdue)
+            .filter(|t| due_day(t).map(|(d, _)| d) == Some(day))
+            .collect();
+        if items.is_empty() {
+            println!("  -");
+        }
+        for t in items {
+            let time = due_day(t).and_then(|(_, tm)| tm).map(|tm| tm.format("%H:%M").to_string()).unwrap_or_default();
+            println!("{}", item_line(t, &format::paint(label(t), &format::pad(&time, 5), color)));
+        }
+        day += Duration::days(1);
+    }
+
+    let later = todos.iter().filter(|t| due_day(t).is_some_and(|(d, _)| d > last)).count();
+    if later > 0 {
+        println!("({} more due after {})", later, last.format("%Y-%m-%d"));
+    }
+
+    if !no_due.is_empty() {
+        println!();
+        println!("{}", format::paint(Label::NoDue, &format!("NO DUE ({})", no_due.len()), color));
+        for t in &no_due {
+            println!("{}", item_line(t, &format::paint(label(t), &format::pad("", 5), color)));
+        }
+    }
+    Ok(())
+}
+
+fn item_line(t: &TodoFile, when: &str) -> String {
+    let tags = if t.fm.tags.is_empty() { String::new() } else { format!(" ({})", t.fm.tags.join(",")) };
+    format!("  {} [{}] {}{}  {}", when, t.fm.importance, t.fm.title, tags, t.short_id())
+}
+
+/// `todo cal [month]`：月のカレンダー（日曜始まり）
+/// - 日付の後ろに期限の件数
+/// - 日付はその日の todo で一番急ぐラベルの色（OVERDUE > BLOCKED > TODAY > SOON）、今日は反転
+pub fn print_cal(cfg: &Config, todos: &[TodoFile], active: &[TodoFile], month: Option<&str>) -> Result<()> {
+    let color = enable_color();
+    let today = Local::now().date_naive();
+    let first = parse_month(month, today)?;
+    let next = if first.month() == 12 {
+        NaiveDate::from_ymd_opt(first.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(first.year(), first.month() + 1, 1)
+    }
+    .ok_or_else(|| anyhow!("invalid month"))?;
+
+    const CELL: usize = 6;
+    println!("{}", format!("{:^w$}", first.format("%Y-%m").to_string(), w = CELL * 7).trim_end());
+    println!("{}", ["Su", "Mo", "Tu", "We", "Th", "Fr", "Sa"].map(|d| format!("{:<w$}", d, w = CELL)).concat().trim_end());
+
+    let mut line = " ".repeat(CELL * first.weekday().num_days_from_sunday() as usize);
+    let mut day = first;
+    while day < next {
+        let due_today: Vec<&TodoFile> = todos.iter().filter(|t| due_day(t).map(|(d, _)| d) == Some(day)).collect();
+        let worst = due_today
+            .iter()
+            .map(|t| format::label_for_in(t, active, cfg.soon_days))
+            .min_by_key(|l| urgency(*l));
+
+        let mut num = format!("{:>2}", day.day());
+        if let Some(l) = worst {
+            num = format::paint(l, &num, color);
+        }
+        if day == today && color {
+            num = format!("\x1b[7m{}\x1b[0m", num);
+        }
+        let count = if due_today.is_empty() { String::new() } else { format!("({})", due_today.len()) };
+        line.push_str(&num);
+        line.push_str(&format::pad(&count, CELL - 2));
+
+        if day.weekday().num_days_from_sunday() == 6 {
+            println!("{}", line.trim_end());
+            line.clear();
+        }
+        day += Duration::days(1);
+    }
+    if !line.trim().is_empty() {
+        println!("{}", line.trim_end());
+    }
+
+    let overdue = todos.iter().filter(|t| format::label_for(t, cfg.soon_days) == Label::Overdue).count();
+    let no_due = todos.iter().filter(|t| t.fm.due.is_none()).count();
+    let in_month = todos.iter().filter(|t| due_day(t).is_some_and(|(d, _)| d >= first && d < next)).count();
+    println!();
+    println!("{} due this month, {} overdue, {} without due", in_month, overdue, no_due);
+    if color {
+        let legend: Vec<String> = [Label::Overdue, Label::Blocked, Label::Today, Label::Soon]
+            .iter()
+            .map(|l| format::label_colored(*l, true))
+            .collect();
+        println!("{}", legend.join(" "));
+    }
+    Ok(())
+}
+
+/// 小さいほど急ぐ
+fn urgency(l: Label) -> u8 {
+    match l {
+        Label::Overdue => 0,
+        Label::Blocked => 1,
+        Label::Today => 2,
+        Label::Soon => 3,
+        Label::None => 4,
+        Label::NoDue => 5,
+    }
+}
+
+/// YYYY-MM / M（今年）/ 省略（今月）→ その月の1日
+fn parse_month(s: Option<&str>, today: NaiveDate) -> Result<NaiveDate> {
+    let Some(s) = s.map(str::trim) else {
+        return Ok(today.with_day(1).unwrap_or(today));
+    };
+    let (year, month) = match s.split_once('-') {
+        Some((y, m)) => (y.parse::<i32>().ok(), m.parse::<u32>().ok()),
+        None => (Some(today.year()), s.trim_end_matches('月').parse::<u32>().ok()),
+    };
+    year.zip(month)
+        .and_then(|(y, m)| NaiveDate::from_ymd_opt(y, m, 1))
+        .ok_or_else(|| anyhow!("invalid month: {} (use YYYY-MM or M)", s))
+}
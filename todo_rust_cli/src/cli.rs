@@ -1,4 +1,13 @@
 use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::export::ExportFormat;
+use crate::ics;
+use crate::import::ImportFormat;
+use crate::output::OutputFormat;
+use crate::report::GroupBy;
+use crate::stats::Period;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -8,8 +17,9 @@ use clap::{Parser, Subcommand};
     long_about = "A simple TODO manager where each TODO is stored as a Markdown file with YAML frontmatter.\n\
                   Default root: ~/todo\n\
                   Directories: active/, done/YYYY/MM/, canceled/YYYY/MM/, templates/\n\
-                  Tip: done/start/wait/cancel/reopen support fzf selection when no argument is given.\n\
-                  Tip: In fzf, Ctrl-O opens the selected file in $EDITOR (if available).\n\
+                  Tip: done/start/wait/cancel/reopen support fzf selection when no argument is given\n\
+                  (a built-in fuzzy picker is used when fzf is not installed; see `picker` in config.toml).\n\
+                  Tip: In fzf / the built-in picker, Ctrl-O opens the selected file in $EDITOR (if available).\n\
                   Tip: todo fix-broken helps repair files quarantined in done/broken or canceled/broken."
 )]
 pub struct Args {
@@ -24,7 +34,8 @@ pub enum Cmd {
         /// Title (optional if --edit)
         title: Option<String>,
 
-        /// Due date: YYYY-MM-DD or RFC3339 datetime (e.g. 2026-01-10 or 2026-01-10T18:00+09:00)
+        /// Due date: YYYY-MM-DD, RFC3339, or relative (today, tomorrow, +3d, 2w, fri, next mon, eow, eom,
+        /// 17:00, "fri 17:00", 明日, 来週月曜, 3日後). Unparsable input is rejected.
         #[arg(long)]
         due: Option<String>,
 
@@ -43,41 +54,148 @@ pub enum Cmd {
         /// Custom slug for filename
         #[arg(long)]
         slug: Option<String>,
+
+        /// Blocked by other todos (comma-separated ids or id prefixes): --blocked-by 2025-12-31T01,2026-01-02T09
+        #[arg(long, value_delimiter = ',')]
+        blocked_by: Vec<String>,
+
+        /// Repeat rule: e.g. "every 1w", "every 3d", "monthly on 25", "weekdays", "weekly on mon,thu".
+        /// When marked done, the next occurrence is created in active/ with a recalculated due.
+        #[arg(long)]
+        repeat: Option<String>,
     },
 
     /// List todos (default: active only, status in todo|doing|waiting)
     List {
-        /// Due within: e.g. 14d
-        #[arg(long)]
-        due_within: Option<String>,
+        #[command(flatten)]
+        filter: ListFilter,
+
+        /// Output format. json/ndjson/csv/tsv emit every frontmatter field plus label, path and short_id
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+
+        /// Table columns in order, comma-separated (table format only):
+        /// label due importance subtasks id title tags status created updated repeat path.
+        /// Default: label,due,importance,subtasks,id,title,tags
+        #[arg(long, value_name = "COLS")]
+        columns: Option<String>,
+    },
+
+    /// List ACTIVE todos with a saved view from config.toml ([views.<name>]: filter fields, sort, columns, format).
+    /// Without a name (or with --list), show the defined views.
+    /// Views also appear as "@name" entries in the no-argument start/wait/done/cancel picker.
+    View {
+        name: Option<String>,
+
+        /// Show the defined views
+        #[arg(long, conflicts_with = "name")]
+        list: bool,
+
+        /// Output format (overrides the view's format)
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Table columns (overrides the view's columns)
+        #[arg(long, value_name = "COLS")]
+        columns: Option<String>,
+    },
 
-        /// Due from (inclusive): YYYY-MM-DD or RFC3339
+    /// ACTIVE todos grouped by due date for the next N days (today first),
+    /// with OVERDUE before and NO DUE after. Times are shown for RFC3339 dues.
+    Agenda {
+        /// Number of days to show, starting today
+        #[arg(long, default_value_t = 7)]
+        days: i64,
+
+        #[command(flatten)]
+        filter: ListFilter,
+    },
+
+    /// Month calendar of ACTIVE todos: per-day due counts, colored by the most urgent label
+    /// (OVERDUE/BLOCKED/TODAY/SOON). Month: YYYY-MM or M (this year); default this month.
+    Cal {
+        month: Option<String>,
+
+        #[command(flatten)]
+        filter: ListFilter,
+    },
+
+    /// Created / done / canceled counts per week or month, lead time (created -> done),
+    /// overdue rate and a per-tag breakdown, from active and archived todos.
+    /// Dates: YYYY-MM-DD, or how far back (7d, 2w, 3m, 1y). Default: the last 12 weeks/months.
+    Stats {
         #[arg(long)]
-        due_from: Option<String>,
+        since: Option<String>,
 
-        /// Due to (inclusive): YYYY-MM-DD or RFC3339
         #[arg(long)]
-        due_to: Option<String>,
+        until: Option<String>,
 
-        /// Filter by a single tag
         #[arg(long)]
         tag: Option<String>,
 
-        /// Filter by status: todo|doing|waiting|done|canceled
-        #[arg(long)]
-        status: Option<String>,
+        #[arg(long, value_enum, default_value_t = Period::Week)]
+        by: Period,
+    },
 
-        /// Importance filter: e.g. >=4, <3, =5, or 3
-        #[arg(long)]
-        importance: Option<String>,
+    /// Export todos (ACTIVE by default) in another format to stdout or a file.
+    /// ics: VTODO (UID=id, DUE, STATUS, PRIORITY from importance, CATEGORIES from tags)
+    /// and/or an all-day / timed VEVENT on the due date.
+    /// Set [ics] path in config.toml to rewrite a subscribable .ics after every change.
+    /// todotxt / taskwarrior: files the other tool can read (same mapping as import, reversed).
+    /// markdown / html: a report grouped by status or tag, with each todo's body sections and log.
+    Export {
+        /// Output format (or use --to)
+        #[arg(value_enum, required_unless_present = "to", conflicts_with = "to")]
+        format: Option<ExportFormat>,
+
+        /// Output format
+        #[arg(long, value_enum)]
+        to: Option<ExportFormat>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
 
-        /// Text query (uses rg if available, else fallback search)
+        /// Include done/canceled todos (also archived ones)
         #[arg(long)]
-        text: Option<String>,
+        all: bool,
+
+        /// ics: components to emit (default: [ics] component in config, else both)
+        #[arg(long, value_enum)]
+        component: Option<ics::Component>,
+
+        /// markdown/html: section headings (default: status)
+        #[arg(long, value_enum)]
+        group_by: Option<GroupBy>,
+
+        #[command(flatten)]
+        filter: ListFilter,
+    },
+
+    /// Create todo files from another tool's data (same file naming as add;
+    /// completed records go straight to done/YYYY/MM or canceled/YYYY/MM).
+    /// Maps priorities to importance, projects/contexts/labels to tags, due dates and completion state.
+    /// Records that cannot be imported (or were imported before) are reported as skipped.
+    /// The whole import is one operation for `todo undo`.
+    Import {
+        /// Source format
+        #[arg(long, value_enum)]
+        from: ImportFormat,
 
-        /// Include overdue items when using --due-within
+        /// Input file ("-" for stdin)
+        file: PathBuf,
+
+        /// Show what would be created without writing anything
         #[arg(long)]
-        include_overdue: bool,
+        dry_run: bool,
+    },
+
+    /// Full-screen interactive list grouped by label (OVERDUE/TODAY/SOON/...), with body preview.
+    /// Keys: j/k move, s/w/d/c start/wait/done/cancel, e edit, r reopen, Tab active/closed,
+    /// / filter (e.g. "tag:work imp:>=4 within:14d overdue text", "? <where expr>"), p preview, q quit
+    Tui {
+        #[command(flatten)]
+        filter: ListFilter,
     },
 
     /// Show a todo file (id or id prefix). If multiple matches, fzf will be used if available.
@@ -88,7 +206,14 @@ pub enum Cmd {
     Edit { id_or_prefix: String },
 
     /// Mark as doing (status=doing). If no argument is provided, fzf-select from ACTIVE todos.
-    Start { id_or_prefix: Option<String> },
+    /// Refused while any blocked_by todo is still active (use --force to start anyway).
+    Start {
+        id_or_prefix: Option<String>,
+
+        /// Start even if blocked
+        #[arg(long)]
+        force: bool,
+    },
 
     /// Mark as waiting (status=waiting). If no argument is provided, fzf-select from ACTIVE todos.
     Wait { id_or_prefix: Option<String> },
@@ -113,4 +238,234 @@ pub enum Cmd {
     /// - Open in $EDITOR
     /// - If it becomes valid, auto-place it into active/ or done/canceled YYYY/MM
     FixBroken,
+
+    /// Show the blocked_by dependency tree (active and archived), and what this todo blocks
+    Deps { id_or_prefix: String },
+
+    /// Edit the "## サブタスク" checklist of an ACTIVE todo in place (a log line is written)
+    Sub {
+        #[command(subcommand)]
+        cmd: SubCmd,
+    },
+
+    /// Time tracking: clock in/out of an ACTIVE todo. Intervals are stored in the frontmatter (clock:)
+    /// and logged; only one todo is clocked in at a time.
+    Clock {
+        #[command(subcommand)]
+        cmd: ClockCmd,
+    },
+
+    /// Pomodoro: count down N minutes (default 25, [focus] minutes in config) for an ACTIVE todo.
+    /// space/p pauses, q/Esc/Ctrl-C aborts (nothing is recorded). A finished session increments
+    /// pomodoros in the frontmatter and is logged. fzf-select if no argument.
+    Focus {
+        id_or_prefix: Option<String>,
+
+        #[arg(long)]
+        minutes: Option<u32>,
+
+        /// Ring the terminal bell at the end (or [focus] bell = true)
+        #[arg(long)]
+        bell: bool,
+
+        /// Send an OSC 9 desktop notification at the end (or [focus] notify = true)
+        #[arg(long)]
+        notify: bool,
+    },
+
+    /// One-line summary for a status bar: overdue / today / soon counts and the current doing todo
+    /// (the clocked-in one first). Reads only active todos through the index, so it is cheap to
+    /// call every few seconds. Placeholders for --format: {overdue} {today} {soon} {active}
+    /// {doing} {doing_count} {clock}
+    StatusLine {
+        /// Template, e.g. '!{overdue} T{today} S{soon} {doing}'
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Emit an OSC 1337 SetUserVar sequence instead of text (WezTerm pane user var, default name: todo_status)
+        #[arg(long, value_name = "NAME", num_args = 0..=1, default_missing_value = crate::statusline::DEFAULT_USER_VAR)]
+        user_var: Option<String>,
+
+        /// Truncate the doing title to this display width
+        #[arg(long, default_value_t = 32)]
+        title_width: usize,
+    },
+
+    /// Reports over recorded data
+    Report {
+        #[command(subcommand)]
+        cmd: ReportCmd,
+    },
+
+    /// Read back the "## ログ" section as typed entries (time, action, status change, text).
+    /// Older date-only lines are read too; new lines carry a full timestamp.
+    /// - With an id (active or archived): that todo's log, oldest first
+    /// - Without: a timeline of every todo's log since --since (default 7d), grouped by day
+    Log {
+        id_or_prefix: Option<String>,
+
+        /// YYYY-MM-DD or how far back (7d, 2w, 3m)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Timeline only: todos with this tag
+        #[arg(long, conflicts_with = "id_or_prefix")]
+        tag: Option<String>,
+
+        /// Only entries with this action (e.g. done, start, subtask add)
+        #[arg(long)]
+        action: Option<String>,
+    },
+
+    /// Rebuild the parse caches (root_dir/.index.json for active/, .index.archive.json for done/ and canceled/).
+    /// The cache is normally kept up to date automatically (path + mtime + size).
+    Reindex,
+
+    /// Check every file under root_dir (active/, done/, canceled/ incl. broken/ and unknown/) and report
+    /// broken files, invalid fields (importance, dates, done_at, repeat), duplicate ids,
+    /// status/directory mismatches, missing "## ログ" and restored_from pointing outside root_dir.
+    /// Exits non-zero while problems remain.
+    Doctor {
+        /// Repair what can be repaired automatically (each fix is logged in the file)
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Revert the last N operations (add, edit, sub, start/wait/done/cancel, reopen, archive, fix-broken)
+    /// recorded in root_dir/.journal.ndjson. Refuses if a file changed since, unless --force.
+    Undo {
+        /// How many operations to revert (newest first)
+        #[arg(default_value_t = 1)]
+        n: usize,
+
+        /// Overwrite files that were modified after the operation
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Commit pending changes, pull --rebase from [git] remote/branch and push.
+    /// On conflicts the rebase is left in progress and conflicting frontmatter keys are listed.
+    Sync,
+
+    /// Show recorded operations, newest first
+    History {
+        /// Number of entries to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+/// Filters shared by `list` and `tui` (also the body of a saved view in config.toml)
+#[derive(clap::Args, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListFilter {
+    /// Due within: e.g. 14d
+    #[arg(long)]
+    pub due_within: Option<String>,
+
+    /// Due from (inclusive): YYYY-MM-DD, RFC3339 or relative (same as add --due)
+    #[arg(long)]
+    pub due_from: Option<String>,
+
+    /// Due to (inclusive): YYYY-MM-DD, RFC3339 or relative (same as add --due)
+    #[arg(long)]
+    pub due_to: Option<String>,
+
+    /// Filter by a single tag
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Filter by status: todo|doing|waiting|done|canceled
+    #[arg(long)]
+    pub status: Option<String>,
+
+    /// Importance filter: e.g. >=4, <3, =5, or 3
+    #[arg(long)]
+    pub importance: Option<String>,
+
+    /// Text query (uses rg if available, else fallback search)
+    #[arg(long)]
+    pub text: Option<String>,
+
+    /// Query expression, e.g. "(tag:work or tag:mail) and imp>=4 and not status=waiting".
+    /// Fields: id title status repeat restored_from importance due created_at updated_at done_at
+    /// tags blocked_by text body. Ops: = != > >= < <= : (contains) ~ !~ (regex) in (a,b) all (a,b).
+    /// Dates accept the --due syntax (today, +7d, eow ...); bare words search title and body.
+    #[arg(long = "where", value_name = "EXPR")]
+    #[serde(rename = "where")]
+    pub where_expr: Option<String>,
+
+    /// Include overdue items when using --due-within
+    #[arg(long)]
+    pub include_overdue: bool,
+
+    /// Sort keys, comma-separated: due importance created updated status title tag id.
+    /// Prefix with - (or append :desc) for descending, e.g. "-importance,due".
+    /// Default: overdue first, then due, importance (high first), id
+    #[arg(long, value_name = "KEYS", allow_hyphen_values = true)]
+    pub sort: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SubCmd {
+    /// Append "- [ ] <text>" to the checklist
+    Add { id_or_prefix: String, text: String },
+
+    /// Check an item: <item> is a 1-based number or (part of) the text
+    Check { id_or_prefix: String, item: String },
+
+    /// Uncheck an item: <item> is a 1-based number or (part of) the text
+    Uncheck { id_or_prefix: String, item: String },
+
+    /// Remove an item: <item> is a 1-based number or (part of) the text
+    Rm { id_or_prefix: String, item: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ClockCmd {
+    /// Start the clock (fzf-select if no argument). Stops any other running clock first
+    /// and sets the todo to doing.
+    In { id_or_prefix: Option<String> },
+
+    /// Stop the running clock (the todo stays doing). Leaving doing (done, wait, ...) also stops it.
+    Out,
+
+    /// Show what is clocked in and for how long
+    Status,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ReportCmd {
+    /// Clocked time per todo, per tag and per day (active and archived todos).
+    /// Default: today. Dates: YYYY-MM-DD or how far back (7d, 2w).
+    Time {
+        /// This week (Monday to Sunday). Cannot be combined with --since / --until.
+        #[arg(long, conflicts_with_all = ["since", "until"])]
+        week: bool,
+
+        #[arg(long)]
+        since: Option<String>,
+
+        #[arg(long)]
+        until: Option<String>,
+
+        #[arg(long)]
+        tag: Option<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_week_conflicts_with_a_range() {
+        for extra in [["--since", "7d"], ["--until", "2026-01-01"]] {
+            let args = ["todo", "report", "time", "--week", extra[0], extra[1]];
+            let err = Args::try_parse_from(args).unwrap_err();
+            assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict, "{:?}", args);
+        }
+        let ok = Args::try_parse_from(["todo", "report", "time", "--since", "7d", "--until", "1d"]).unwrap();
+        assert!(matches!(ok.cmd, Cmd::Report { cmd: ReportCmd::Time { week: false, .. } }));
+    }
 }
@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, TimeZone};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::format;
+use crate::model::{ClockEntry, Status, TodoFile};
+use crate::{activity, due, filter, journal, store};
+
+fn parse(s: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(s).ok()
+}
+
+fn now() -> DateTime<FixedOffset> {
+    Local::now().fixed_offset()
+}
+
+/// 計測中なら開始時刻
+pub fn running(t: &TodoFile) -> Option<DateTime<FixedOffset>> {
+    t.fm.clock.iter().find(|c| c.end.is_none()).and_then(|c| parse(&c.start))
+}
+
+/// [from, to) に入っている分の合計秒（計測中の区間は now まで）
+fn seconds_between(t: &TodoFile, from: Option<DateTime<FixedOffset>>, to: Option<DateTime<FixedOffset>>, now: DateTime<FixedOffset>) -> i64 {
+    t.fm.clock
+        .iter()
+        .filter_map(|c| {
+            let start = parse(&c.start)?;
+            let end = match c.end.as_deref() {
+                Some(e) => parse(e)?,
+                None => now,
+            };
+            let start = from.map_or(start, |f| start.max(f));
+            let end = to.map_or(end, |x| end.min(x));
+            Some((end - start).num_seconds().max(0))
+        })
+        .sum()
+}
+
+/// 全区間の合計秒
+pub fn total_seconds(t: &TodoFile) -> i64 {
+    seconds_between(t, None, None, now())
+}
+
+/// 1h05m / 25m（1分未満は 0m）
+pub fn fmt_duration(secs: i64) -> String {
+    let m = secs.max(0) / 60;
+    if m >= 60 {
+        format!("{}h{:02}m", m / 60, m % 60)
+    } else {
+        format!("{}m", m)
+    }
+}
+
+/// 計測中の区間を閉じてログに残す（計測中でなければ None）
+/// status 変更（doing から外れたとき）からも呼ばれる。保存は呼び出し側
+pub fn stop(t: &mut TodoFile) -> Option<i64> {
+    let at = activity::stamp();
+    let entry = t.fm.clock.iter_mut().find(|c| c.end.is_none())?;
+    let secs = parse(&entry.start).and_then(|s| parse(&at).map(|e| (e - s).num_seconds().max(0))).unwrap_or(0);
+    entry.end = Some(at.clone());
+    t.fm.updated_at = store::now_jst_rfc3339();
+    let msg = format!("clock out: {} (total {})", fmt_duration(secs), fmt_duration(total_seconds(t)));
+    t.append_log_line(&at, &msg);
+    Some(secs)
+}
+
+/// `todo clock in`：計測を始める
+/// - 他の todo が計測中ならそちらを先に止める（計測中は常に1つだけ）
+/// - doing でなければ doing にする（start と同じログが残る）
+pub fn clock_in(cfg: &Config, id_or_prefix: &str) -> Result<()> {
+    let todo = store::resolve_one(cfg, id_or_prefix)?;
+    let _lock = store::lock_root(cfg)?;
+    let todo = store::reload(&todo)?;
+    if !todo.fm.status.is_active() {
+        return Err(anyhow!("cannot clock in to a {} todo: {}", todo.fm.status.as_str(), todo.fm.title));
+    }
+    if running(&todo).is_some() {
+        println!("already clocked in: {}", todo.fm.title);
+        return Ok(());
+    }
+
+    let mut tx = journal::begin("clock", format!("clock in: {}", todo.fm.title));
+    let res = (|| -> Result<Vec<String>> {
+        let mut out = Vec::new();
+        for mut other in store::load_active(cfg)?.into_iter().filter(|t| t.fm.id != todo.fm.id) {
+            if let Some(secs) = stop(&mut other) {
+                store::save(&mut tx, &other)?;
+                out.push(format!("clocked out: {} ({})", other.fm.title, fmt_duration(secs)));
+            }
+        }
+
+        if todo.fm.status != Status::Doing {
+            out.extend(crate::set_status(cfg, &mut tx, todo.clone(), Status::Doing)?);
+        }
+        let mut todo = store::resolve_one(cfg, &todo.fm.id)?;
+        let at = activity::stamp();
+        todo.fm.clock.push(ClockEntry { start: at.clone(), end: None });
+        todo.fm.updated_at = store::now_jst_rfc3339();
+        todo.append_log_line(&at, "clock in");
+        store::save(&mut tx, &todo)?;
+        out.push(format!("clocked in: {} (total so far {})", todo.fm.title, fmt_duration(total_seconds(&todo))));
+        Ok(out)
+    })();
+    crate::finish_tx(cfg, tx)?;
+    for line in res? {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// `todo clock out`：計測中の区間を閉じる（status は doing のまま）
+pub fn clock_out(cfg: &Config) -> Result<()> {
+    let _lock = store::lock_root(cfg)?;
+    let active = store::load_active(cfg)?;
+    let Some(mut todo) = active.into_iter().find(|t| running(t).is_some()) else {
+        println!("not clocked in");
+        return Ok(());
+    };
+    let mut tx = journal::begin("clock", format!("clock out: {}", todo.fm.title));
+    let secs = stop(&mut todo).unwrap_or(0);
+    let res = store::save(&mut tx, &todo);
+    crate::finish_tx(cfg, tx)?;
+    res?;
+    println!("clocked out: {} ({}, total {})", todo.fm.title, fmt_duration(secs), fmt_duration(total_seconds(&todo)));
+    Ok(())
+}
+
+/// `todo clock status`
+pub fn print_status(cfg: &Config) -> Result<()> {
+    let active = store::load_active(cfg)?;
+    match active.iter().find_map(|t| running(t).map(|s| (t, s))) {
+        Some((t, start)) => println!(
+            "clocked in: {} since {} ({}, total {})  {}",
+            t.fm.title,
+            start.with_timezone(&Local).format("%H:%M"),
+            fmt_duration((now() - start).num_seconds()),
+            fmt_duration(total_seconds(t)),
+            t.short_id()
+        ),
+        None => println!("not clocked in"),
+    }
+    Ok(())
+}
+
+/// `todo report time`：期間内の作業時間を todo・タグ・日ごとに
+/// - 既定は今日、--week は今週（月曜〜日曜）。--since / --until で任意の期間
+/// - 区間は期間の境目で切って数える。計測中の区間は今まで
+pub fn report(cfg: &Config, week: bool, since: Option<&str>, until: Option<&str>, tag: Option<&str>) -> Result<()> {
+    if week && (since.is_some() || until.is_some()) {
+        return Err(anyhow!("--week cannot be combined with --since / --until"));
+    }
+    let today = Local::now().date_naive();
+    let (from, to) = if week {
+        let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        (monday, monday + Duration::days(6))
+    } else {
+        let from = since.map(due::parse_past).transpose()?.unwrap_or(today);
+        let to = until.map(due::parse_past).transpose()?.unwrap_or(today);
+        (from, to)
+    };
+    if from > to {
+        return Err(anyhow!("--since {} is after --until {}", from, to));
+    }
+
+    let mut todos = store::load_all(cfg)?;
+    if let Some(tag) = tag {
+        filter::retain_tag(&mut todos, tag);
+    }
+    let now = now();
+    let start_of = |d: NaiveDate| -> Option<DateTime<FixedOffset>> {
+        Local.from_local_datetime(&d.and_hms_opt(0, 0, 0)?).earliest().map(|d| d.fixed_offset())
+    };
+    let range = (start_of(from), start_of(to + Duration::days(1)));
+
+    let mut by_todo: Vec<(&TodoFile, i64)> = todos
+        .iter()
+        .map(|t| (t, seconds_between(t, range.0, range.1, now)))
+        .filter(|(_, s)| *s > 0)
+        .collect();
+    by_todo.sort_by_key(|(_, s)| Reverse(*s));
+    let total: i64 = by_todo.iter().map(|(_, s)| s).sum();
+
+    let mut by_tag: BTreeMap<String, i64> = BTreeMap::new();
+    for (t, s) in &by_todo {
+        if t.fm.tags.is_empty() {
+            *by_tag.entry("(no tag)".to_string()).or_default() += s;
+        }
+        for tag in &t.fm.tags {
+            *by_tag.entry(tag.clone()).or_default() += s;
+        }
+    }
+
+    let running_now = todos.iter().find(|t| running(t).is_some());
+    let mut title = format!("{} .. {}  total {}", from, to, fmt_duration(total));
+    if let Some(t) = running_now {
+        title.push_str(&format!("  (clocked in: {})", t.fm.title));
+    }
+    println!("{}", title);
+    if by_todo.is_empty() {
+        println!("no clocked time");
+        return Ok(());
+    }
+
+    println!();
+    println!("by todo");
+    for (t, s) in &by_todo {
+        let tags = if t.fm.tags.is_empty() { String::new() } else { format!(" ({})", t.fm.tags.join(",")) };
+        println!("  {}  {}{}  {}", format::pad(&fmt_duration(*s), 6), t.fm.title, tags, t.short_id());
+    }
+
+    println!();
+    println!("by tag");
+    let mut tags: Vec<(String, i64)> = by_tag.into_iter().collect();
+    tags.sort_by_key(|(_, s)| Reverse(*s));
+    let w = tags.iter().map(|(n, _)| format::width(n)).max().unwrap_or(0);
+    let max = tags.first().map(|(_, s)| *s).unwrap_or(0);
+    for (name, s) in &tags {
+        println!("  {}  {}  {}", format::pad(&fmt_duration(*s), 6), format::pad(name, w), bar(*s, max));
+    }
+
+    if from < to {
+        println!();
+        println!("by day");
+        let days: Vec<(NaiveDate, i64)> = std::iter::successors(Some(from), |d| Some(*d + Duration::days(1)))
+            .take_while(|d| *d <= to)
+            .map(|d| {
+                let r = (start_of(d), start_of(d + Duration::days(1)));
+                (d, by_todo.iter().map(|(t, _)| seconds_between(t, r.0, r.1, now)).sum())
+            })
+            .collect();
+        let max = days.iter().map(|(_, s)| *s).max().unwrap_or(0);
+        for (d, s) in days {
+            let line = format!("  {}  {}  {}", d.format("%a %m-%d"), format::pad(&fmt_duration(s), 6), bar(s, max));
+            println!("{}", line.trim_end());
+        }
+    }
+    Ok(())
+}
+
+fn bar(n: i64, max: i64) -> String {
+    format::bar(n.max(0) as usize, max.max(0) as usize)
+}
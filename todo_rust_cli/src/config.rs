@@ -1,13 +1,156 @@
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use crate::cli::ListFilter;
+use crate::ics::Component;
+use crate::output::OutputFormat;
+
+/// 引数なしの done/start 等で使う選択 UI
+/// - auto: fzf があれば fzf、無ければ内蔵ピッカー
+/// - fzf: fzf のみ（無ければ選択なし扱い）
+/// - builtin: 常に内蔵ピッカー
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PickerMode {
+    #[default]
+    Auto,
+    Fzf,
+    Builtin,
+}
+
+/// root_dir を git リポジトリとして扱う設定（[git] セクション）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitConfig {
+    /// 更新系コマンドのたびに変更したファイルだけを commit する
+    #[serde(default)]
+    pub auto_commit: bool,
+
+    /// todo sync の相手
+    #[serde(default = "default_remote")]
+    pub remote: String,
+
+    /// 省略時は現在のブランチ
+    #[serde(default)]
+    pub branch: Option<String>,
+}
+
+fn default_remote() -> String {
+    "origin".to_string()
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self { auto_commit: false, remote: default_remote(), branch: None }
+    }
+}
+
+/// iCalendar の購読用ファイル（[ics] セクション）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IcsConfig {
+    /// 設定すると更新系コマンドのたびにここへ書き出す
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+
+    /// done/canceled（archive 済みも含む）も載せる
+    #[serde(default)]
+    pub include_closed: bool,
+
+    /// include_closed のとき、done_at が直近この日数のものだけ載せる（0 なら全部。archive 全体を毎回読まないように）
+    #[serde(default = "default_ics_closed_days")]
+    pub closed_days: u32,
+
+    #[serde(default)]
+    pub component: Component,
+}
+
+fn default_ics_closed_days() -> u32 {
+    30
+}
+
+impl Default for IcsConfig {
+    fn default() -> Self {
+        Self { path: None, include_closed: false, closed_days: default_ics_closed_days(), component: Component::default() }
+    }
+}
+
+/// `todo focus` の既定値（[focus] セクション）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusConfig {
+    #[serde(default = "default_focus_minutes")]
+    pub minutes: u32,
+
+    /// 終わったらベル（BEL）を鳴らす
+    #[serde(default)]
+    pub bell: bool,
+
+    /// 終わったら OSC 9 で端末に通知を出させる（WezTerm / iTerm2 / Windows Terminal など）
+    #[serde(default)]
+    pub notify: bool,
+}
+
+fn default_focus_minutes() -> u32 {
+    25
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        Self { minutes: default_focus_minutes(), bell: false, notify: false }
+    }
+}
+
+/// 名前付きの絞り込み（[views.<name>] セクション）
+/// キーは list のオプションと同じ（due_within, tag, status, importance, text, where, sort, columns, ...）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct View {
+    #[serde(default)]
+    pub description: Option<String>,
+
+    #[serde(flatten)]
+    pub filter: ListFilter,
+
+    /// 省略時は table
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+
+    /// table の列（--columns と同じ書式）
+    #[serde(default)]
+    pub columns: Option<String>,
+}
+
+/// 書かなかったキーは Default の値
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub root_dir: PathBuf,
     pub soon_days: i64,
     pub editor: String,
     pub archive: bool,
     pub auto_archive: bool,
+
+    #[serde(default)]
+    pub picker: PickerMode,
+
+    #[serde(default)]
+    pub git: GitConfig,
+
+    #[serde(default)]
+    pub views: BTreeMap<String, View>,
+
+    #[serde(default)]
+    pub ics: IcsConfig,
+
+    #[serde(default)]
+    pub focus: FocusConfig,
+
+    /// .journal.ndjson に残す操作の数（undo / history で遡れる範囲。0 なら切り詰めない）
+    #[serde(default = "default_journal_keep_last")]
+    pub journal_keep_last: usize,
+}
+
+fn default_journal_keep_last() -> usize {
+    200
 }
 
 impl Default for Config {
@@ -19,23 +162,29 @@ impl Default for Config {
             editor: std::env::var("EDITOR").unwrap_or_else(|_| "nvim".to_string()),
             archive: true,
             auto_archive: false,
+            picker: PickerMode::Auto,
+            git: GitConfig::default(),
+            views: BTreeMap::new(),
+            ics: IcsConfig::default(),
+            focus: FocusConfig::default(),
+            journal_keep_last: default_journal_keep_last(),
         }
     }
 }
 
 impl Config {
-    pub fn load() -> Self {
-        let mut cfg = Config::default();
-        if let Some(mut path) = dirs::config_dir() {
-            path.push("todo");
-            path.push("config.toml");
-            if let Ok(s) = std::fs::read_to_string(&path) {
-                if let Ok(user_cfg) = toml::from_str::<Config>(&s) {
-                    cfg = user_cfg;
-                }
-            }
-        }
-        cfg
+    /// ~/.config/todo/config.toml（無ければ既定値）
+    /// 書き間違いは黙って既定値に戻さずエラーにする（root_dir が別の場所になったまま動かないように）
+    pub fn load() -> Result<Self> {
+        let Some(path) = dirs::config_dir().map(|d| d.join("todo").join("config.toml")) else {
+            return Ok(Config::default());
+        };
+        let s = match std::fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(anyhow!("failed to read {}: {}", path.display(), e)),
+        };
+        toml::from_str(&s).map_err(|e| anyhow!("invalid config {}: {}", path.display(), e))
     }
 
     pub fn active_dir(&self) -> PathBuf { self.root_dir.join("active") }
@@ -43,4 +192,39 @@ impl Config {
     pub fn canceled_dir(&self) -> PathBuf { self.root_dir.join("canceled") }
     pub fn templates_dir(&self) -> PathBuf { self.root_dir.join("templates") }
     pub fn template_path(&self) -> PathBuf { self.templates_dir().join("todo.md") }
+    pub fn index_path(&self) -> PathBuf { self.root_dir.join(".index.json") }
+    pub fn archive_index_path(&self) -> PathBuf { self.root_dir.join(".index.archive.json") }
+    pub fn lock_path(&self) -> PathBuf { self.root_dir.join(".lock") }
+    pub fn journal_path(&self) -> PathBuf { self.root_dir.join(".journal.ndjson") }
+
+    pub fn view(&self, name: &str) -> Result<&View> {
+        self.views.get(name).ok_or_else(|| {
+            let names: Vec<&str> = self.views.keys().map(String::as_str).collect();
+            if names.is_empty() {
+                anyhow!("unknown view: {} (no views defined; add [views.{}] to config.toml)", name, name)
+            } else {
+                anyhow!("unknown view: {} (defined: {})", name, names.join(", "))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_take_defaults() {
+        let cfg: Config = toml::from_str("root_dir = \"/srv/todo\"\n[views.work]\ntag = \"work\"\n").unwrap();
+        assert_eq!(cfg.root_dir, PathBuf::from("/srv/todo"));
+        assert_eq!(cfg.soon_days, 7);
+        assert_eq!(cfg.journal_keep_last, 200);
+        assert!(cfg.view("work").is_ok());
+    }
+
+    #[test]
+    fn a_bad_view_is_an_error_not_the_default_config() {
+        let e = toml::from_str::<Config>("root_dir = \"/srv/todo\"\n[views.work]\nformat = \"yaml\"\n").unwrap_err();
+        assert!(e.to_string().contains("yaml"), "{}", e);
+    }
 }
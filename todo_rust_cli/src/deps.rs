@@ -0,0 +1,131 @@
+use crate::model::TodoFile;
+
+/// blocked_by の1要素（id または id prefix）を解決する
+/// 完全一致を優先し、無ければ一意な prefix 一致のみ
+pub fn find_ref<'a>(list: &'a [TodoFile], id_ref: &str) -> Option<&'a TodoFile> {
+    if let Some(t) = list.iter().find(|t| t.fm.id == id_ref) {
+        return Some(t);
+    }
+    let mut hits = list.iter().filter(|t| t.fm.id.starts_with(id_ref));
+    let first = hits.next()?;
+    if hits.next().is_some() { None } else { Some(first) }
+}
+
+/// まだ終わっていない（status が todo/doing/waiting の）blocker
+pub fn active_blockers<'a>(todo: &TodoFile, active: &'a [TodoFile]) -> Vec<&'a TodoFile> {
+    todo.fm
+        .blocked_by
+        .iter()
+        .filter_map(|r| find_ref(active, r))
+        .filter(|b| b.fm.status.is_active() && b.fm.id != todo.fm.id)
+        .collect()
+}
+
+pub fn is_blocked(todo: &TodoFile, active: &[TodoFile]) -> bool {
+    !active_blockers(todo, active).is_empty()
+}
+
+/// todo を blocked_by に含むもの（逆方向の依存）
+pub fn dependents<'a>(todo: &TodoFile, all: &'a [TodoFile]) -> Vec<&'a TodoFile> {
+    all.iter()
+        .filter(|t| t.fm.id != todo.fm.id)
+        .filter(|t| t.fm.blocked_by.iter().any(|r| find_ref(all, r).map(|b| b.fm.id == todo.fm.id).unwrap_or(false)))
+        .collect()
+}
+
+fn node_line(t: &TodoFile) -> String {
+    format!("{}  [{}]  {}", t.short_id(), t.fm.status.as_str(), t.fm.title)
+}
+
+/// blocked_by を再帰的にたどったツリー（循環は "(cycle)" で打ち切る）
+pub fn render_tree(root: &TodoFile, all: &[TodoFile]) -> String {
+    let mut out = node_line(root);
+    out.push('\n');
+    let mut stack = vec![root.fm.id.clone()];
+    render_children(root, all, "", &mut stack, &mut out);
+    out
+}
+
+fn render_children(node: &TodoFile, all: &[TodoFile], prefix: &str, stack: &mut Vec<String>, out: &mut String) {
+    let n = node.fm.blocked_by.len();
+    for (i, r) in node.fm.blocked_by.iter().enumerate() {
+        let last = i + 1 == n;
+        let (branch, cont) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+
+        let Some(child) = find_ref(all, r) else {
+            out.push_str(&format!("{}{}{}  (not found)\n", prefix, branch, r));
+            continue;
+        };
+
+        if stack.contains(&child.fm.id) {
+            out.push_str(&format!("{}{}{}  (cycle)\n", prefix, branch, node_line(child)));
+            continue;
+        }
+
+        out.push_str(&format!("{}{}{}\n", prefix, branch, node_line(child)));
+        stack.push(child.fm.id.clone());
+        render_children(child, all, &format!("{}{}", prefix, cont), stack, out);
+        stack.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter::parse_todo_file;
+    use crate::model::Status;
+
+    fn todo(id: &str, status: Status, blocked_by: &[&str]) -> TodoFile {
+        let text = format!(
+            "---\nid: {}\ntitle: {}\nstatus: todo\nimportance: 3\ncreated_at: 2026-01-01T09:00:00+09:00\nupdated_at: 2026-01-01T09:00:00+09:00\n---\n",
+            id, id
+        );
+        let mut t = parse_todo_file(std::path::PathBuf::from(format!("{}.md", id)), &text).unwrap();
+        t.fm.status = status;
+        t.fm.blocked_by = blocked_by.iter().map(|s| s.to_string()).collect();
+        t
+    }
+
+    #[test]
+    fn find_ref_prefers_exact_then_unique_prefix() {
+        let list = vec![todo("abc", Status::Todo, &[]), todo("abcd", Status::Todo, &[]), todo("xyz1", Status::Todo, &[])];
+        assert_eq!(find_ref(&list, "abc").unwrap().fm.id, "abc");
+        assert_eq!(find_ref(&list, "xy").unwrap().fm.id, "xyz1");
+        assert!(find_ref(&list, "ab").is_none(), "ambiguous prefix");
+        assert!(find_ref(&list, "q").is_none());
+    }
+
+    #[test]
+    fn only_active_blockers_block() {
+        let a = todo("a", Status::Todo, &["b", "c", "gone", "a"]);
+        let active = vec![a.clone(), todo("b", Status::Waiting, &[]), todo("c", Status::Done, &[])];
+        let ids: Vec<&str> = active_blockers(&a, &active).iter().map(|t| t.fm.id.as_str()).collect();
+        // 完了済み・見つからない・自分自身は blocker にならない
+        assert_eq!(ids, ["b"]);
+        assert!(is_blocked(&a, &active));
+
+        // blocker がアーカイブ済みなら active の一覧には無い
+        let archived = todo("b", Status::Done, &[]);
+        let active = vec![a.clone()];
+        assert!(!is_blocked(&a, &active));
+        let all = vec![a.clone(), archived];
+        assert!(!is_blocked(&a, &all));
+        assert_eq!(dependents(&all[1], &all).len(), 1);
+    }
+
+    #[test]
+    fn render_tree_stops_at_cycles() {
+        let all = vec![todo("a", Status::Todo, &["b"]), todo("b", Status::Doing, &["a", "zz"])];
+        let out = render_tree(&all[0], &all);
+        assert_eq!(
+            out,
+            "a  [todo]  a\n└── b  [doing]  b\n    ├── a  [todo]  a  (cycle)\n    └── zz  (not found)\n"
+        );
+    }
+
+    #[test]
+    fn render_tree_shows_archived_blockers() {
+        let all = vec![todo("a", Status::Todo, &["old"]), todo("old", Status::Done, &[])];
+        assert_eq!(render_tree(&all[0], &all), "a  [todo]  a\n└── old  [done]  old\n");
+    }
+}
@@ -0,0 +1,270 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, SecondsFormat};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::config::Config;
+use crate::frontmatter::parse_todo_file;
+use crate::model::{Status, TodoFile};
+use crate::journal::{self, Tx};
+use crate::{activity, store, validate};
+
+/// root_dir 配下（active/ done/ canceled/、broken/ unknown/ も含む）を全件検査する
+/// - broken：frontmatter が読めない
+/// - invalid：validate::check の不正（importance 範囲外、日付が読めない、done_at 無し など）
+/// - duplicate-id：同じ id が複数ファイルにある
+/// - misplaced：status とディレクトリが合わない（done/ に todo、canceled/ に done、YYYY/MM 違い、broken/ にある読めるファイル）
+/// - missing-log：`## ログ` が無い
+/// - orphaned-restored-from：restored_from が root_dir の外を指している
+///
+/// --fix では直せるものだけ直す（直したことはログに残す）。
+/// 直せないもの（broken、due/created_at/repeat の不正）は報告のみ。
+/// 直した分は1操作として journal に残す（todo undo で戻せ、auto-commit / ics にも反映される）
+pub fn run(cfg: &Config, fix: bool) -> Result<()> {
+    if !fix {
+        return check_all(cfg, None);
+    }
+    let _lock = store::lock_root(cfg)?;
+    let mut tx = journal::begin("doctor", "doctor --fix");
+    let res = check_all(cfg, Some(&mut tx));
+    crate::finish_tx(cfg, tx)?;
+    res
+}
+
+/// tx があれば --fix（直した分をそこに記録する）
+fn check_all(cfg: &Config, mut tx: Option<&mut Tx>) -> Result<()> {
+    let fix = tx.is_some();
+    let mut todos = Vec::new();
+    let mut found = 0;
+    let mut fixed = 0;
+
+    for path in scan(cfg) {
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| parse_todo_file(path.clone(), &text));
+        match parsed {
+            Ok(t) => todos.push(t),
+            Err(e) => {
+                found += 1;
+                report(cfg, "broken", &path, &first_line(&e.to_string()));
+            }
+        }
+    }
+
+    let dup_paths = duplicate_ids(cfg, &todos);
+    let mut ids: HashSet<String> = todos.iter().map(|t| t.fm.id.clone()).collect();
+    let at = activity::stamp();
+
+    for mut todo in todos {
+        let mut fixes: Vec<String> = Vec::new();
+        let mut problems = 0;
+        let misplaced = misplaced(cfg, &todo);
+
+        for issue in validate::check(&todo.fm) {
+            problems += 1;
+            report(cfg, "invalid", &todo.path, &format!("{}: {}", issue.field, issue.message));
+            if fix {
+                if let Some(msg) = fix_field(&mut todo, issue.field, &mut ids) {
+                    fixes.push(msg);
+                }
+            }
+        }
+
+        if dup_paths.contains(&todo.path) {
+            problems += 1;
+            if fix {
+                let old = std::mem::replace(&mut todo.fm.id, new_id(&ids));
+                ids.insert(todo.fm.id.clone());
+                fixes.push(format!("new id {} (was duplicate {})", todo.fm.id, old));
+            }
+        }
+
+        if !has_log_section(&todo.body) {
+            problems += 1;
+            report(cfg, "missing-log", &todo.path, "no '## ログ' section");
+            if fix {
+                fixes.push("added log section".to_string());
+            }
+        }
+
+        if let Some(r) = todo.fm.restored_from.as_deref() {
+            if !Path::new(r).starts_with(&cfg.root_dir) {
+                problems += 1;
+                report(cfg, "orphaned-restored-from", &todo.path, &format!("points outside root_dir: {}", r));
+                if fix {
+                    fixes.push(format!("cleared restored_from ({})", r));
+                    todo.fm.restored_from = None;
+                }
+            }
+        }
+
+        if let Some(why) = &misplaced {
+            problems += 1;
+            report(cfg, "misplaced", &todo.path, why);
+        }
+
+        found += problems;
+        let Some(tx) = tx.as_deref_mut().filter(|_| problems > 0) else {
+            continue;
+        };
+
+        if !fixes.is_empty() {
+            todo.fm.updated_at = store::now_jst_rfc3339();
+            for f in &fixes {
+                todo.append_log_line(&at, &format!("doctor: {}", f));
+            }
+            // 直せない不正が残っていても、直せた分は書き込む
+            store::write_todo(tx, &todo)?;
+            for f in &fixes {
+                println!("  fixed: {}", f);
+            }
+            fixed += fixes.len();
+        }
+
+        if misplaced.is_some() {
+            if !validate::check(&todo.fm).is_empty() {
+                println!("  cannot move: fix the frontmatter first");
+                continue;
+            }
+            let dest = store::place_todo_by_status(cfg, tx, &todo)?;
+            println!("  fixed: moved to {}", rel(cfg, &dest));
+            fixed += 1;
+        }
+    }
+
+    if found == 0 {
+        println!("no problems found");
+        return Ok(());
+    }
+    if fix {
+        println!("{} problem(s) found, {} fixed", found, fixed);
+        if fixed < found {
+            return Err(anyhow!("{} problem(s) need manual repair", found - fixed));
+        }
+        return Ok(());
+    }
+    Err(anyhow!("{} problem(s) found (run: todo doctor --fix)", found))
+}
+
+fn scan(cfg: &Config) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    for dir in [cfg.active_dir(), cfg.done_dir(), cfg.canceled_dir()] {
+        if !dir.exists() {
+            continue;
+        }
+        for entry in WalkDir::new(&dir).sort_by_file_name().into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() { continue; }
+            if entry.path().extension().and_then(|s| s.to_str()) != Some("md") { continue; }
+            out.push(entry.path().to_path_buf());
+        }
+    }
+    out
+}
+
+/// 同じ id を持つファイル群を報告し、2件目以降（created_at → パス順）のパスを返す
+fn duplicate_ids(cfg: &Config, todos: &[TodoFile]) -> HashSet<PathBuf> {
+    let mut by_id: HashMap<&str, Vec<&TodoFile>> = HashMap::new();
+    for t in todos {
+        by_id.entry(t.fm.id.as_str()).or_default().push(t);
+    }
+
+    let mut out = HashSet::new();
+    let mut groups: Vec<_> = by_id.into_iter().filter(|(id, g)| g.len() > 1 && !id.trim().is_empty()).collect();
+    groups.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (id, mut group) in groups {
+        group.sort_by(|a, b| a.fm.created_at.cmp(&b.fm.created_at).then(a.path.cmp(&b.path)));
+        let keep = group[0];
+        for t in &group[1..] {
+            report(cfg, "duplicate-id", &t.path, &format!("id {} is also used by {}", id, rel(cfg, &keep.path)));
+            out.insert(t.path.clone());
+        }
+    }
+    out
+}
+
+/// 自動で直せるフィールドだけ直す（直した内容を返す）
+fn fix_field(todo: &mut TodoFile, field: &str, ids: &mut HashSet<String>) -> Option<String> {
+    let fm = &mut todo.fm;
+    match field {
+        "id" => {
+            fm.id = new_id(ids);
+            ids.insert(fm.id.clone());
+            Some(format!("new id {} (was empty)", fm.id))
+        }
+        "importance" => {
+            let old = fm.importance;
+            fm.importance = old.clamp(validate::IMPORTANCE_MIN, validate::IMPORTANCE_MAX);
+            Some(format!("importance {} -> {}", old, fm.importance))
+        }
+        "updated_at" => {
+            fm.updated_at = store::now_jst_rfc3339();
+            Some("updated_at reset".to_string())
+        }
+        "done_at" if matches!(fm.status, Status::Done | Status::Canceled) => {
+            let at = Some(fm.updated_at.clone())
+                .filter(|s| DateTime::parse_from_rfc3339(s).is_ok())
+                .unwrap_or_else(store::now_jst_rfc3339);
+            fm.done_at = Some(at.clone());
+            Some(format!("done_at set to {}", at))
+        }
+        "done_at" => {
+            fm.done_at = None;
+            Some("cleared unparsable done_at".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// status と置き場所が合っていなければ理由を返す
+/// （active/ の done/canceled は archive 待ちなので正常扱い）
+fn misplaced(cfg: &Config, todo: &TodoFile) -> Option<String> {
+    let path = &todo.path;
+    let status = todo.fm.status.as_str();
+
+    let (base, expected) = if path.starts_with(cfg.done_dir()) {
+        (cfg.done_dir(), Status::Done)
+    } else if path.starts_with(cfg.canceled_dir()) {
+        (cfg.canceled_dir(), Status::Canceled)
+    } else {
+        return None;
+    };
+
+    if todo.fm.status != expected {
+        return Some(format!("status {} in {}/", status, expected.as_str()));
+    }
+    if path.starts_with(base.join("broken")) {
+        return Some("parses fine but is in broken/".to_string());
+    }
+    let dir = todo.fm.done_at.as_deref().and_then(|d| store::month_dir(&base, d).ok())?;
+    if path.parent() != Some(dir.as_path()) {
+        return Some(format!("done_at {} but not in {}", todo.fm.done_at.as_deref().unwrap_or(""), rel(cfg, &dir)));
+    }
+    None
+}
+
+fn has_log_section(body: &str) -> bool {
+    body.lines().any(|l| l.trim_end() == "## ログ")
+}
+
+fn new_id(ids: &HashSet<String>) -> String {
+    loop {
+        let id = Local::now().to_rfc3339_opts(SecondsFormat::Nanos, false);
+        if !ids.contains(&id) {
+            return id;
+        }
+    }
+}
+
+fn report(cfg: &Config, kind: &str, path: &Path, detail: &str) {
+    println!("[{}] {}: {}", kind, rel(cfg, path), detail);
+}
+
+fn rel(cfg: &Config, path: &Path) -> String {
+    path.strip_prefix(&cfg.root_dir).unwrap_or(path).display().to_string()
+}
+
+fn first_line(s: &str) -> String {
+    s.lines().next().unwrap_or("").to_string()
+}
@@ -0,0 +1,385 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveTime, TimeZone, Weekday};
+
+/// `--due` の入力を保存用の正規形にする
+/// - 日付のみ → `YYYY-MM-DD`
+/// - 時刻つき → RFC3339（ローカルのオフセット）
+///
+/// 受け付ける書式：
+/// - `2026-01-10` / RFC3339 / `01-10` / `1/10`（今年、過ぎていれば来年）
+/// - `today` `tomorrow` `yesterday` / `今日` `明日` `明後日`
+/// - `+3d` `2w` `+1m` `1y` / `3日後` `2週間後` `1ヶ月後`
+/// - `mon`..`sun`（今日より後の最初の曜日）/ `next mon`（来週の曜日）/ `月曜` `来週月曜` `今週金曜`
+/// - `eod` `eow`（日曜）`eom` `eoy` / `next week` `next month` / `週末` `今週末` `月末` `今月末` `来月` `年末`
+/// - 時刻：`17:00` `5pm` `17時` `17時30分` `17時半`（単独なら今日、過ぎていれば明日。日付の後ろに付けても可）
+pub fn normalize(input: &str) -> Result<String> {
+    normalize_at(input, Local::now())
+}
+
+/// `--since` / `--until` 用：過去の日付
+/// - `7d` `-2w` `3m` `1y` / `7d ago` `2 weeks ago` / `3日前` `2週間前` → 今日からさかのぼる
+/// - それ以外は `--due` と同じ書式（`2026-01-01` `yesterday` ...）の日付部分
+pub fn parse_past(input: &str) -> Result<NaiveDate> {
+    let today = Local::now().date_naive();
+    let s = input.trim().to_lowercase();
+    let back = if let Some(rest) = s.strip_prefix('-') {
+        rest.to_string()
+    } else if let Some(rest) = s.strip_suffix("ago") {
+        rest.trim_end().to_string()
+    } else if let Some(ja) = s.strip_suffix('前') {
+        format!("{}後", ja)
+    } else {
+        s.clone()
+    };
+    if let Some(ahead) = parse_offset(&back, today) {
+        return today
+            .checked_sub_signed(ahead - today)
+            .ok_or_else(|| anyhow!("invalid date: {}", input.trim()));
+    }
+    let n = normalize(input).map_err(|_| anyhow!("invalid date: {} (use YYYY-MM-DD, 7d, 2w, 3m, yesterday ...)", input.trim()))?;
+    NaiveDate::parse_from_str(&n[..10.min(n.len())], "%Y-%m-%d").map_err(|_| anyhow!("invalid date: {}", input.trim()))
+}
+
+fn normalize_at(input: &str, now: DateTime<Local>) -> Result<String> {
+    let s = input.trim();
+    if s.is_empty() {
+        return Err(anyhow!("empty due"));
+    }
+
+    if DateTime::parse_from_rfc3339(s).is_ok() {
+        return Ok(s.to_string());
+    }
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(d.format("%Y-%m-%d").to_string());
+    }
+
+    let lower = s.to_lowercase();
+    let (date_part, time) = split_time(&lower);
+    let today = now.date_naive();
+
+    let date = if date_part.is_empty() {
+        let Some(t) = time else {
+            return Err(invalid(input));
+        };
+        // 時刻だけなら次に来るその時刻（夜に `17:00` と打って最初から期限切れにしない）
+        if today.and_time(t) <= now.naive_local() {
+            today.succ_opt().ok_or_else(|| invalid(input))?
+        } else {
+            today
+        }
+    } else {
+        parse_date(date_part, today).ok_or_else(|| invalid(input))?
+    };
+
+    match time {
+        None => Ok(date.format("%Y-%m-%d").to_string()),
+        Some(t) => {
+            let dt = Local
+                .from_local_datetime(&date.and_time(t))
+                .earliest()
+                .ok_or_else(|| invalid(input))?;
+            Ok(dt.to_rfc3339_opts(chrono::SecondsFormat::Secs, false))
+        }
+    }
+}
+
+fn invalid(input: &str) -> anyhow::Error {
+    anyhow!(
+        "invalid due: {} (use YYYY-MM-DD, RFC3339, today/tomorrow, +3d, 2w, fri, next mon, eow, eom, 17:00, 明日, 来週月曜 ...)",
+        input.trim()
+    )
+}
+
+/// 末尾の時刻指定を切り出す
+fn split_time(s: &str) -> (&str, Option<NaiveTime>) {
+    // "17:00" / "9:30"
+    let digits_start = s
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_ascii_digit() || *c == ':')
+        .last()
+        .map(|(i, _)| i);
+    if let Some(i) = digits_start {
+        let tail = &s[i..];
+        if tail.contains(':') {
+            if let Ok(t) = NaiveTime::parse_from_str(tail, "%H:%M") {
+                return (s[..i].trim(), Some(t));
+            }
+        }
+    }
+
+    // "5pm" / "11am"
+    for (suffix, offset) in [("pm", 12), ("am", 0)] {
+        if let Some(rest) = s.strip_suffix(suffix) {
+            let (head, num) = split_trailing_digits(rest);
+            if let Ok(h) = num.parse::<u32>() {
+                if (1..=12).contains(&h) {
+                    let h = if h == 12 { offset } else { h + offset };
+                    return (head.trim(), NaiveTime::from_hms_opt(h, 0, 0));
+                }
+            }
+        }
+    }
+
+    // "17時" / "17時30分" / "17時半"
+    let (rest, minute) = if let Some(r) = s.strip_suffix("半") {
+        (r, Some(30))
+    } else if let Some(r) = s.strip_suffix('分') {
+        let (head, num) = split_trailing_digits(r);
+        match num.parse::<u32>() {
+            Ok(m) => (head, Some(m)),
+            Err(_) => return (s, None),
+        }
+    } else {
+        (s, Some(0))
+    };
+    if let Some(r) = rest.strip_suffix('時') {
+        let (head, num) = split_trailing_digits(r);
+        if let (Ok(h), Some(m)) = (num.parse::<u32>(), minute) {
+            if let Some(t) = NaiveTime::from_hms_opt(h, m, 0) {
+                return (head.trim(), Some(t));
+            }
+        }
+    }
+
+    (s, None)
+}
+
+fn split_trailing_digits(s: &str) -> (&str, &str) {
+    let i = s
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_ascii_digit())
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(s.len());
+    (&s[..i], &s[i..])
+}
+
+fn parse_date(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let s = s.trim();
+
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Some(d);
+    }
+
+    match s {
+        "today" | "now" | "eod" | "今日" | "今日中" | "本日" => return Some(today),
+        "tomorrow" | "明日" | "あした" | "あす" => return today.succ_opt(),
+        "yesterday" | "昨日" => return today.pred_opt(),
+        "明後日" | "あさって" => return today.checked_add_signed(Duration::days(2)),
+        "eow" | "週末" | "今週末" => return Some(end_of_week(today)),
+        "eom" | "月末" | "今月末" => return end_of_month(today),
+        "eoy" | "年末" => return NaiveDate::from_ymd_opt(today.year(), 12, 31),
+        "next week" | "来週" => return Some(week_start(today) + Duration::weeks(1)),
+        "next month" | "来月" => return today.with_day(1)?.checked_add_months(Months::new(1)),
+        "next year" | "来年" => return NaiveDate::from_ymd_opt(today.year() + 1, 1, 1),
+        _ => {}
+    }
+
+    if let Some(d) = parse_offset(s, today) {
+        return Some(d);
+    }
+    if let Some(d) = parse_month_day(s, today) {
+        return Some(d);
+    }
+
+    // next mon / 来週月曜 / 来週の月曜日
+    for prefix in ["next ", "来週の", "来週"] {
+        if let Some(rest) = s.strip_prefix(prefix) {
+            let wd = parse_weekday(rest.trim())?;
+            return Some(week_start(today) + Duration::weeks(1) + Duration::days(wd.num_days_from_monday() as i64));
+        }
+    }
+    // 今週金曜 / this fri
+    for prefix in ["this ", "今週の", "今週"] {
+        if let Some(rest) = s.strip_prefix(prefix) {
+            let wd = parse_weekday(rest.trim())?;
+            return Some(week_start(today) + Duration::days(wd.num_days_from_monday() as i64));
+        }
+    }
+    // mon / 月曜 → 今日より後の最初の曜日
+    if let Some(wd) = parse_weekday(s) {
+        let mut d = today.succ_opt()?;
+        while d.weekday() != wd {
+            d = d.succ_opt()?;
+        }
+        return Some(d);
+    }
+
+    None
+}
+
+/// +3d / 2w / 1m / 1y / 3日後 / 2週間後 / 1ヶ月後 / 1年後
+/// （日本語の単位は「後」が必須。`3日` は「3日後」ではなく日付の意味になりがちなため）
+fn parse_offset(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let s = s.strip_prefix('+').unwrap_or(s);
+    let digits: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() {
+        return None;
+    }
+    let n: u32 = digits.parse().ok()?;
+    let unit = s[digits.len()..].trim();
+
+    let days = |k: i64| today.checked_add_signed(Duration::days(k));
+    let months = |k: u32| today.checked_add_months(Months::new(k));
+
+    if let Some(ja) = unit.strip_suffix('後') {
+        return match ja {
+            "日" => days(n as i64),
+            "週" | "週間" => days(n as i64 * 7),
+            "ヶ月" | "か月" | "カ月" | "ケ月" => months(n),
+            "年" => months(n * 12),
+            _ => None,
+        };
+    }
+
+    match unit {
+        "d" | "day" | "days" => days(n as i64),
+        "w" | "week" | "weeks" => days(n as i64 * 7),
+        "m" | "month" | "months" => months(n),
+        "y" | "year" | "years" => months(n * 12),
+        _ => None,
+    }
+}
+
+/// 01-20 / 1/20 / 1月20日（過ぎていれば来年）
+fn parse_month_day(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let (m, d) = if let Some((m, d)) = s.split_once('-').or_else(|| s.split_once('/')) {
+        (m, d)
+    } else {
+        let (m, d) = s.strip_suffix('日')?.split_once('月')?;
+        (m, d)
+    };
+    let (m, d): (u32, u32) = (m.trim().parse().ok()?, d.trim().parse().ok()?);
+    let this_year = NaiveDate::from_ymd_opt(today.year(), m, d)?;
+    if this_year >= today {
+        Some(this_year)
+    } else {
+        NaiveDate::from_ymd_opt(today.year() + 1, m, d)
+    }
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    if let Ok(wd) = s.parse::<Weekday>() {
+        return Some(wd);
+    }
+    let s = s.strip_suffix('日').unwrap_or(s);
+    let s = s.strip_suffix('曜').unwrap_or(s);
+    match s {
+        "月" => Some(Weekday::Mon),
+        "火" => Some(Weekday::Tue),
+        "水" => Some(Weekday::Wed),
+        "木" => Some(Weekday::Thu),
+        "金" => Some(Weekday::Fri),
+        "土" => Some(Weekday::Sat),
+        "日" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn week_start(d: NaiveDate) -> NaiveDate {
+    d - Duration::days(d.weekday().num_days_from_monday() as i64)
+}
+
+fn end_of_week(d: NaiveDate) -> NaiveDate {
+    week_start(d) + Duration::days(6)
+}
+
+fn end_of_month(d: NaiveDate) -> Option<NaiveDate> {
+    d.with_day(1)?.checked_add_months(Months::new(1))?.pred_opt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-10-14（水）の指定時刻
+    fn at(h: u32, m: u32) -> DateTime<Local> {
+        Local.from_local_datetime(&NaiveDate::from_ymd_opt(2026, 10, 14).unwrap().and_hms_opt(h, m, 0).unwrap()).unwrap()
+    }
+
+    fn date(input: &str) -> String {
+        normalize_at(input, at(10, 0)).unwrap()
+    }
+
+    /// 時刻つきの結果を "YYYY-MM-DD HH:MM" に
+    fn local(input: &str, now: DateTime<Local>) -> String {
+        let s = normalize_at(input, now).unwrap();
+        DateTime::parse_from_rfc3339(&s).unwrap().with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+    }
+
+    #[test]
+    fn absolute_dates_pass_through() {
+        assert_eq!(date("2026-01-10"), "2026-01-10");
+        assert_eq!(date("2026-01-10T17:00:00+09:00"), "2026-01-10T17:00:00+09:00");
+    }
+
+    #[test]
+    fn relative_words() {
+        assert_eq!(date("today"), "2026-10-14");
+        assert_eq!(date("Tomorrow"), "2026-10-15");
+        assert_eq!(date("明日"), "2026-10-15");
+        assert_eq!(date("明後日"), "2026-10-16");
+        assert_eq!(date("+3d"), "2026-10-17");
+        assert_eq!(date("2w"), "2026-10-28");
+        assert_eq!(date("1m"), "2026-11-14");
+        assert_eq!(date("3日後"), "2026-10-17");
+        assert_eq!(date("2週間後"), "2026-10-28");
+    }
+
+    #[test]
+    fn weekdays_and_period_ends() {
+        assert_eq!(date("fri"), "2026-10-16");
+        assert_eq!(date("wed"), "2026-10-21");
+        assert_eq!(date("next mon"), "2026-10-19");
+        assert_eq!(date("来週月曜"), "2026-10-19");
+        assert_eq!(date("今週金曜"), "2026-10-16");
+        assert_eq!(date("eow"), "2026-10-18");
+        assert_eq!(date("eom"), "2026-10-31");
+        assert_eq!(date("月末"), "2026-10-31");
+        assert_eq!(date("eoy"), "2026-12-31");
+    }
+
+    #[test]
+    fn month_day_rolls_to_next_year() {
+        assert_eq!(date("12-24"), "2026-12-24");
+        assert_eq!(date("1/10"), "2027-01-10");
+        assert_eq!(date("1月10日"), "2027-01-10");
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(local("tomorrow 17:00", at(10, 0)), "2026-10-15 17:00");
+        assert_eq!(local("明日17時半", at(10, 0)), "2026-10-15 17:30");
+        assert_eq!(local("fri 5pm", at(10, 0)), "2026-10-16 17:00");
+        assert_eq!(local("9時30分", at(8, 0)), "2026-10-14 09:30");
+    }
+
+    #[test]
+    fn bare_time_rolls_over_once_passed() {
+        assert_eq!(local("17:00", at(10, 0)), "2026-10-14 17:00");
+        assert_eq!(local("17:00", at(17, 0)), "2026-10-15 17:00");
+        assert_eq!(local("17:00", at(20, 30)), "2026-10-15 17:00");
+        assert_eq!(local("5pm", at(20, 30)), "2026-10-15 17:00");
+        // 日付を書いたときはそのまま（今日の過ぎた時刻も受け付ける）
+        assert_eq!(local("today 17:00", at(20, 30)), "2026-10-14 17:00");
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(normalize_at("", at(10, 0)).is_err());
+        assert!(normalize_at("someday", at(10, 0)).is_err());
+        assert!(normalize_at("2026-13-01", at(10, 0)).is_err());
+        assert!(normalize_at("25:00", at(10, 0)).is_err());
+    }
+
+    #[test]
+    fn past_offsets() {
+        let week_ago = Local::now().date_naive() - Duration::days(7);
+        for s in ["7d", "-7d", "7d ago", "1 week ago", "7日前"] {
+            assert_eq!(parse_past(s).unwrap(), week_ago, "{}", s);
+        }
+        assert!(parse_past("7 parsecs ago").is_err());
+    }
+}
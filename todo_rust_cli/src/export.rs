@@ -0,0 +1,200 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use clap::ValueEnum;
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+use crate::ics;
+use crate::model::{Status, TodoFile};
+use crate::report::{self, GroupBy};
+use crate::store;
+
+/// `todo export` の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// iCalendar (VTODO / VEVENT)
+    Ics,
+    /// todo.txt (one line per todo)
+    Todotxt,
+    /// Taskwarrior JSON (for `task import`)
+    Taskwarrior,
+    /// Markdown report with body sections and log
+    Markdown,
+    /// Standalone HTML report with body sections and log
+    Html,
+}
+
+impl ExportFormat {
+    pub fn is_report(self) -> bool {
+        matches!(self, ExportFormat::Markdown | ExportFormat::Html)
+    }
+}
+
+/// output が無ければ stdout へ
+/// - heading は markdown / html の見出しに添える説明（フィルタなど）
+pub fn run(
+    format: ExportFormat,
+    todos: &[TodoFile],
+    output: Option<&Path>,
+    component: ics::Component,
+    group_by: GroupBy,
+    heading: &str,
+) -> Result<()> {
+    let text = match format {
+        ExportFormat::Ics => ics::render(todos, component),
+        ExportFormat::Todotxt => todotxt(todos),
+        ExportFormat::Taskwarrior => taskwarrior(todos)?,
+        ExportFormat::Markdown => report::markdown(todos, group_by, heading),
+        ExportFormat::Html => report::html(todos, group_by, heading),
+    };
+    match output {
+        Some(path) => {
+            store::write_atomic(path, text.as_bytes())?;
+            println!("exported {} todo(s) to {}", todos.len(), path.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+// ---- todo.txt ----
+
+/// import の逆：`x 完了日 作成日 title +tag due:YYYY-MM-DD pri:A`
+/// - importance 5,4,2,1 → (A),(B),(D),(E)。3 は優先度なし（import で 3 に戻る）
+/// - 完了行は優先度を pri: に移す（todo.txt の慣習）
+/// - canceled は完了行に status:canceled、doing / waiting は status: を付ける
+fn todotxt(todos: &[TodoFile]) -> String {
+    let mut out = String::new();
+    for t in todos {
+        let fm = &t.fm;
+        let closed = !fm.status.is_active();
+        let pri = match fm.importance {
+            i if i >= 5 => Some('A'),
+            4 => Some('B'),
+            3 => None,
+            2 => Some('D'),
+            _ => Some('E'),
+        };
+
+        let mut words: Vec<String> = Vec::new();
+        if closed {
+            words.push("x".to_string());
+            let done = fm.done_at.as_deref().and_then(local_date);
+            let created = local_date(&fm.created_at);
+            // 完了日が無いと作成日を完了日と読まれるので、その場合は作成日も出さない
+            if let Some(d) = done {
+                words.push(d.format("%Y-%m-%d").to_string());
+                if let Some(c) = created {
+                    words.push(c.format("%Y-%m-%d").to_string());
+                }
+            }
+        } else {
+            if let Some(p) = pri {
+                words.push(format!("({})", p));
+            }
+            if let Some(c) = local_date(&fm.created_at) {
+                words.push(c.format("%Y-%m-%d").to_string());
+            }
+        }
+        words.push(fm.title.split_whitespace().collect::<Vec<_>>().join(" "));
+        for tag in &fm.tags {
+            words.push(format!("+{}", tag.split_whitespace().collect::<Vec<_>>().join("_")));
+        }
+        if let Some(d) = fm.due.as_deref().and_then(due_date) {
+            words.push(format!("due:{}", d.format("%Y-%m-%d")));
+        }
+        if closed {
+            if let Some(p) = pri {
+                words.push(format!("pri:{}", p));
+            }
+        }
+        if !matches!(fm.status, Status::Todo | Status::Done) {
+            words.push(format!("status:{}", fm.status.as_str()));
+        }
+        out.push_str(&words.join(" "));
+        out.push('\n');
+    }
+    out
+}
+
+// ---- Taskwarrior ----
+
+/// `task import` で読める JSON 配列（uuid は付けないので取り込み側で新規になる）
+/// - importance 5 → H、4 → M、2 以下 → L、3 は priority なし
+/// - doing は pending + start、waiting は pending + タグ "waiting"（wait 日付が無いため）
+/// - done → completed、canceled → deleted（end は done_at）
+fn taskwarrior(todos: &[TodoFile]) -> Result<String> {
+    let mut tasks = Vec::new();
+    for t in todos {
+        let fm = &t.fm;
+        let mut m = Map::new();
+        m.insert("description".into(), json!(fm.title));
+        let status = match fm.status {
+            Status::Todo | Status::Doing | Status::Waiting => "pending",
+            Status::Done => "completed",
+            Status::Canceled => "deleted",
+        };
+        m.insert("status".into(), json!(status));
+        if let Some(s) = tw_date(&fm.created_at) {
+            m.insert("entry".into(), json!(s));
+        }
+        if let Some(s) = tw_date(&fm.updated_at) {
+            m.insert("modified".into(), json!(s));
+            if fm.status == Status::Doing {
+                m.insert("start".into(), json!(s));
+            }
+        }
+        if !fm.status.is_active() {
+            if let Some(s) = fm.done_at.as_deref().and_then(tw_date) {
+                m.insert("end".into(), json!(s));
+            }
+        }
+        if let Some(s) = fm.due.as_deref().and_then(tw_due) {
+            m.insert("due".into(), json!(s));
+        }
+        let priority = match fm.importance {
+            i if i >= 5 => Some("H"),
+            4 => Some("M"),
+            3 => None,
+            _ => Some("L"),
+        };
+        if let Some(p) = priority {
+            m.insert("priority".into(), json!(p));
+        }
+        // Taskwarrior のタグは空白を含められない
+        let mut tags: Vec<String> = fm.tags.iter().map(|s| s.split_whitespace().collect::<Vec<_>>().join("_")).collect();
+        if fm.status == Status::Waiting {
+            tags.push("waiting".to_string());
+        }
+        if !tags.is_empty() {
+            m.insert("tags".into(), json!(tags));
+        }
+        tasks.push(Value::Object(m));
+    }
+    let mut s = serde_json::to_string_pretty(&tasks)?;
+    s.push('\n');
+    Ok(s)
+}
+
+fn tw_date(rfc3339: &str) -> Option<String> {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .ok()
+        .map(|d| d.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+/// 日付だけの due はローカルの 0:00（import で日付に戻る）
+fn tw_due(due: &str) -> Option<String> {
+    if let Ok(d) = NaiveDate::parse_from_str(due, "%Y-%m-%d") {
+        let local = Local.from_local_datetime(&d.and_hms_opt(0, 0, 0)?).earliest()?;
+        return Some(local.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string());
+    }
+    tw_date(due)
+}
+
+fn local_date(rfc3339: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(rfc3339).ok().map(|d| d.with_timezone(&Local).date_naive())
+}
+
+fn due_date(due: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(due, "%Y-%m-%d").ok().or_else(|| local_date(due))
+}
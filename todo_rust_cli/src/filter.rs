@@ -0,0 +1,185 @@
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use std::collections::HashSet;
+use std::process::Command;
+
+use crate::cli::ListFilter;
+use crate::config::Config;
+use crate::model::{Status, TodoFile};
+use crate::{due, query, sort, store};
+
+/// `todo list` のフィルタを適用し、既定の順序（期限切れ → 期限 → 重要度 → id）か --sort の順で並べる
+pub fn apply(cfg: &Config, mut todos: Vec<TodoFile>, f: &ListFilter) -> Result<Vec<TodoFile>> {
+    if let Some(expr) = f.where_expr.as_deref() {
+        let q = query::parse(expr)?;
+        todos.retain(|t| q.matches(t));
+    }
+
+    if let Some(s) = f.status.as_deref() {
+        let want: Status = s.parse()?;
+        todos.retain(|t| t.fm.status == want);
+    }
+
+    if let Some(tag) = f.tag.as_ref() {
+        retain_tag(&mut todos, tag);
+    }
+
+    if let Some(expr) = f.importance.as_deref() {
+        let (op, n) = parse_importance_expr(expr)?;
+        todos.retain(|t| compare_i32(t.fm.importance, op, n));
+    }
+
+    if let Some(q) = f.text.as_deref() {
+        if let Some(paths) = rg_paths(&cfg.root_dir, q) {
+            todos.retain(|t| paths.contains(&t.path));
+        } else {
+            let q = q.to_lowercase();
+            todos.retain(|t| t.fm.title.to_lowercase().contains(&q) || t.body.to_lowercase().contains(&q));
+        }
+    }
+
+    let now_fixed = Local::now().with_timezone(Local::now().offset());
+
+    if let Some(within) = f.due_within.as_deref() {
+        let days = parse_days(within)?;
+        let end = now_fixed + chrono::Duration::days(days);
+        todos.retain(|t| {
+            let Some(due_str) = t.fm.due.as_deref() else { return false; };
+            let Some(due_dt) = store::parse_due_dt(due_str) else { return false; };
+            if !f.include_overdue && due_dt < now_fixed { return false; }
+            due_dt >= now_fixed && due_dt <= end
+        });
+    } else if f.due_from.is_some() || f.due_to.is_some() {
+        let from_dt = f.due_from.as_deref().map(parse_due_arg).transpose()?;
+        let to_dt = f.due_to.as_deref().map(parse_due_arg).transpose()?;
+        todos.retain(|t| {
+            let Some(due_str) = t.fm.due.as_deref() else { return false; };
+            let Some(due_dt) = store::parse_due_dt(due_str) else { return false; };
+            if let Some(from) = from_dt { if due_dt < from { return false; } }
+            if let Some(to) = to_dt { if due_dt > to { return false; } }
+            true
+        });
+    }
+
+    todos.sort_by(|a, b| {
+        use std::cmp::Ordering;
+
+        let a_due = a.fm.due.as_deref().and_then(store::parse_due_dt);
+        let b_due = b.fm.due.as_deref().and_then(store::parse_due_dt);
+
+        let a_over = a_due.map(|d| d < now_fixed).unwrap_or(false);
+        let b_over = b_due.map(|d| d < now_fixed).unwrap_or(false);
+
+        match (a_over, b_over) {
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            _ => {}
+        }
+
+        match (a_due, b_due) {
+            (Some(ad), Some(bd)) => if ad != bd { return ad.cmp(&bd); },
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            (None, None) => {}
+        }
+
+        if a.fm.importance != b.fm.importance {
+            return b.fm.importance.cmp(&a.fm.importance);
+        }
+
+        a.fm.id.cmp(&b.fm.id)
+    });
+
+    // --sort は既定の順序の上から安定ソートする（指定キーで同じなら既定の順序）
+    if let Some(keys) = f.sort.as_deref() {
+        sort::sort(&mut todos, &sort::parse(keys)?);
+    }
+
+    Ok(todos)
+}
+
+/// --due-from / --due-to：`todo add --due` と同じ書式（相対指定も可）
+/// tag を持つものだけ残す（大文字小文字は区別しない）
+pub fn retain_tag(todos: &mut Vec<TodoFile>, tag: &str) {
+    let tag = tag.to_lowercase();
+    todos.retain(|t| t.fm.tags.iter().any(|x| x.to_lowercase() == tag));
+}
+
+fn parse_due_arg(s: &str) -> Result<chrono::DateTime<chrono::FixedOffset>> {
+    let norm = due::normalize(s)?;
+    store::parse_due_dt(&norm).ok_or_else(|| anyhow!("invalid due: {}", s))
+}
+
+pub fn parse_days(s: &str) -> Result<i64> {
+    let s = s.trim().to_lowercase();
+    if let Some(num) = s.strip_suffix('d') {
+        return Ok(num.parse::<i64>()?);
+    }
+    Err(anyhow!("invalid duration: {} (use like 14d)", s))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op { Eq, Gt, Ge, Lt, Le }
+
+fn parse_importance_expr(s: &str) -> Result<(Op, i32)> {
+    let s = s.trim();
+    for (p, op) in [
+        (">=", Op::Ge),
+        ("<=", Op::Le),
+        (">", Op::Gt),
+        ("<", Op::Lt),
+        ("=", Op::Eq),
+    ] {
+        if let Some(rest) = s.strip_prefix(p) {
+            return Ok((op, rest.trim().parse()?));
+        }
+    }
+    Ok((Op::Eq, s.parse()?))
+}
+
+fn compare_i32(v: i32, op: Op, n: i32) -> bool {
+    match op {
+        Op::Eq => v == n,
+        Op::Gt => v > n,
+        Op::Ge => v >= n,
+        Op::Lt => v < n,
+        Op::Le => v <= n,
+    }
+}
+
+fn rg_paths(dir: &std::path::Path, query: &str) -> Option<HashSet<std::path::PathBuf>> {
+    let out = Command::new("rg")
+        .arg("-l")
+        .arg(query)
+        .arg(dir)
+        .output()
+        .ok()?;
+
+    if !out.status.success() { return None; }
+
+    let s = String::from_utf8_lossy(&out.stdout);
+    let mut set = HashSet::new();
+    for line in s.lines() {
+        let p = line.trim();
+        if !p.is_empty() {
+            set.insert(std::path::PathBuf::from(p));
+        }
+    }
+    Some(set)
+}
+
+/// フィルタを TUI の filter 入力と同じ書式で表示（tui のステータス行、view --list、ピッカーの view 候補）
+pub fn describe(f: &ListFilter) -> String {
+    let mut v = Vec::new();
+    if let Some(s) = &f.tag { v.push(format!("tag:{}", s)); }
+    if let Some(s) = &f.status { v.push(format!("status:{}", s)); }
+    if let Some(s) = &f.importance { v.push(format!("imp:{}", s)); }
+    if let Some(s) = &f.due_within { v.push(format!("within:{}", s)); }
+    if let Some(s) = &f.due_from { v.push(format!("from:{}", s)); }
+    if let Some(s) = &f.due_to { v.push(format!("to:{}", s)); }
+    if f.include_overdue { v.push("overdue".to_string()); }
+    if let Some(s) = &f.sort { v.push(format!("sort:{}", s)); }
+    if let Some(s) = &f.text { v.push(s.clone()); }
+    if let Some(s) = &f.where_expr { v.push(format!("? {}", s)); }
+    v.join(" ")
+}
@@ -0,0 +1,159 @@
+use anyhow::{anyhow, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, terminal};
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::format;
+use crate::model::Status;
+use crate::{activity, journal, store};
+
+/// カウントダウン中だけ raw モードにする（キー入力を1文字ずつ読むため）
+struct RawGuard;
+
+impl RawGuard {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(std::io::stdout(), cursor::Hide)?;
+        Ok(RawGuard)
+    }
+}
+
+impl Drop for RawGuard {
+    fn drop(&mut self) {
+        let _ = execute!(std::io::stdout(), cursor::Show);
+        let _ = terminal::disable_raw_mode();
+        println!();
+    }
+}
+
+enum Outcome {
+    Done,
+    Aborted(Duration),
+}
+
+/// 手を動かせる状態（todo / doing）。waiting や完了済みにはポモドーロを数えない
+fn focusable(status: &Status) -> bool {
+    matches!(status, Status::Todo | Status::Doing)
+}
+
+/// `todo focus`：ポモドーロ1回分のカウントダウン
+/// - 端末なら1行を上書きして残り時間とバーを出す（space / p で一時停止、q / Esc / Ctrl-C で中断）
+/// - 最後までやりきったら pomodoros を1増やし、ログに `focus: 25m (pomodoro #n)` を残す
+/// - 中断したとき、終わるまでに完了・waiting・削除されていたときは何も書かない
+/// - bell / notify で終わりに BEL / OSC 9 を出す
+pub fn run(cfg: &Config, id_or_prefix: &str, minutes: Option<u32>, bell: bool, notify: bool) -> Result<()> {
+    let todo = store::resolve_one(cfg, id_or_prefix)?;
+    if !focusable(&todo.fm.status) {
+        return Err(anyhow!("cannot focus on a {} todo: {}", todo.fm.status.as_str(), todo.fm.title));
+    }
+    let minutes = minutes.unwrap_or(cfg.focus.minutes);
+    if minutes == 0 {
+        return Err(anyhow!("--minutes must be at least 1"));
+    }
+    let total = Duration::from_secs(minutes as u64 * 60);
+
+    let interactive = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    let outcome = if interactive {
+        countdown(&todo.fm.title, total)?
+    } else {
+        println!("focus: {} ({}m)", todo.fm.title, minutes);
+        std::thread::sleep(total);
+        Outcome::Done
+    };
+
+    if let Outcome::Aborted(elapsed) = outcome {
+        println!("aborted after {} (not counted)", clock_face(elapsed));
+        return Ok(());
+    }
+
+    // 計測中に編集・完了・移動されているかもしれないので読み直す
+    let _lock = store::lock_root(cfg)?;
+    let current = match store::reload(&todo) {
+        Ok(t) => Some(t),
+        Err(_) => store::load_all(cfg)?.into_iter().find(|t| t.fm.id == todo.fm.id),
+    };
+    let mut todo = match current {
+        Some(t) if focusable(&t.fm.status) => t,
+        Some(t) => {
+            println!("not recorded: {} is now {}", t.fm.title, t.fm.status.as_str());
+            return Ok(());
+        }
+        None => {
+            println!("not recorded: {} was removed during the session", todo.fm.title);
+            return Ok(());
+        }
+    };
+    todo.fm.pomodoros += 1;
+    todo.fm.updated_at = store::now_jst_rfc3339();
+    todo.append_log_line(&activity::stamp(), &format!("focus: {}m (pomodoro #{})", minutes, todo.fm.pomodoros));
+    let mut tx = journal::begin("focus", format!("focus: {}", todo.fm.title));
+    let res = store::save(&mut tx, &todo);
+    crate::finish_tx(cfg, tx)?;
+    res?;
+
+    println!("focus done: {} ({}m, pomodoro #{})", todo.fm.title, minutes, todo.fm.pomodoros);
+    let mut out = std::io::stdout();
+    if bell || cfg.focus.bell {
+        write!(out, "\x07")?;
+    }
+    if notify || cfg.focus.notify {
+        // 制御文字は通知の文字列に入れない
+        let title: String = todo.fm.title.chars().filter(|c| !c.is_control()).collect();
+        write!(out, "\x1b]9;todo: focus done - {}\x07", title)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn countdown(title: &str, total: Duration) -> Result<Outcome> {
+    let _guard = RawGuard::enter()?;
+    let mut out = std::io::stdout();
+    let mut elapsed = Duration::ZERO;
+    let mut last = Instant::now();
+    let mut paused = false;
+
+    loop {
+        if !paused {
+            elapsed += last.elapsed();
+        }
+        last = Instant::now();
+        if elapsed >= total {
+            return Ok(Outcome::Done);
+        }
+
+        let remaining = total - elapsed;
+        let bar = format::gauge(elapsed.as_secs_f64() / total.as_secs_f64());
+        let state = if paused { "paused  " } else { "" };
+        let help = "  [space: pause  q: quit]";
+        let term = terminal::size().ok().map(|(w, _)| w as usize).filter(|w| *w > 0).unwrap_or(80);
+        let head = format!("{} {}  {}", clock_face(remaining), bar, state);
+        let room = term.saturating_sub(format::width(&head) + format::width(help) + 1);
+        write!(out, "\r\x1b[2K{}{}{}", head, format::truncate(title, room), help)?;
+        out.flush()?;
+
+        if event::poll(Duration::from_millis(250))? {
+            if let Event::Key(k) = event::read()? {
+                if k.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match k.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(Outcome::Aborted(elapsed)),
+                    KeyCode::Char('c') if k.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(Outcome::Aborted(elapsed))
+                    }
+                    KeyCode::Char(' ') | KeyCode::Char('p') => paused = !paused,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// mm:ss
+fn clock_face(d: Duration) -> String {
+    // 残り時間は切り上げて 25:00 から始める
+    let secs = d.as_secs() + u64::from(d.subsec_nanos() > 0);
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
@@ -1,9 +1,13 @@
 use chrono::{DateTime, Duration, FixedOffset, Local};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+use crate::deps;
 use crate::model::TodoFile;
 use crate::store::parse_due_dt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Label {
+    Blocked,
     Overdue,
     Today,
     Soon,
@@ -29,8 +33,18 @@ pub fn label_for(todo: &TodoFile, soon_days: i64) -> Label {
     Label::None
 }
 
+/// label_for に加えて、blocked_by に未完了の todo があれば BLOCKED
+/// active は active/ の全件（フィルタ前）を渡す
+pub fn label_for_in(todo: &TodoFile, active: &[TodoFile], soon_days: i64) -> Label {
+    if todo.fm.status.is_active() && deps::is_blocked(todo, active) {
+        return Label::Blocked;
+    }
+    label_for(todo, soon_days)
+}
+
 pub fn label_str(l: Label) -> &'static str {
     match l {
+        Label::Blocked => "BLOCKED",
         Label::Overdue => "OVERDUE",
         Label::Today => "TODAY",
         Label::Soon => "SOON",
@@ -39,14 +53,31 @@ pub fn label_str(l: Label) -> &'static str {
     }
 }
 
+/// 機械可読出力用のキー（json/csv の label 列）
+pub fn label_key(l: Label) -> &'static str {
+    match l {
+        Label::Blocked => "blocked",
+        Label::Overdue => "overdue",
+        Label::Today => "today",
+        Label::Soon => "soon",
+        Label::NoDue => "no_due",
+        Label::None => "none",
+    }
+}
+
 fn color(s: &str, code: &str, enable: bool) -> String {
     if !enable { return s.to_string(); }
     format!("\x1b[{}m{}\x1b[0m", code, s)
 }
 
 pub fn label_colored(l: Label, enable: bool) -> String {
-    let s = label_str(l);
+    paint(l, label_str(l), enable)
+}
+
+/// 任意の文字列をラベルと同じ色で塗る（agenda / cal の日付など）
+pub fn paint(l: Label, s: &str, enable: bool) -> String {
     match l {
+        Label::Blocked => color(s, "35;1", enable),
         Label::Overdue => color(s, "31;1", enable),
         Label::Today => color(s, "33;1", enable),
         Label::Soon => color(s, "36;1", enable),
@@ -70,9 +101,141 @@ pub fn due_display(todo: &TodoFile) -> String {
     }
 }
 
-pub fn truncate(s: &str, max_chars: usize) -> String {
-    if s.chars().count() <= max_chars { return s.to_string(); }
-    let mut out: String = s.chars().take(max_chars.saturating_sub(1)).collect();
+/// 端末上の表示幅
+/// - 書記素クラスタ（結合文字・異体字セレクタ・ZWJ で繋がった絵文字）を1文字として数える
+/// - 全角・CJK・絵文字は 2 桁、結合文字や制御文字は 0 桁
+pub fn width(s: &str) -> usize {
+    s.graphemes(true).map(grapheme_width).sum()
+}
+
+/// 端末は1つの書記素を最大 2 桁で描く（ZWJ 絵文字を部品ごとに数えない）
+fn grapheme_width(g: &str) -> usize {
+    UnicodeWidthStr::width(g).min(2)
+}
+
+/// 表示幅 width に合わせる：truncate した上で右側を空白で埋める
+pub fn pad(s: &str, width: usize) -> String {
+    let mut out = truncate(s, width);
+    let n = self::width(&out);
+    out.push_str(&" ".repeat(width.saturating_sub(n)));
+    out
+}
+
+/// 表示幅が max_width を超えるなら末尾を … にして切り詰める（書記素の途中では切らない）
+pub fn truncate(s: &str, max_width: usize) -> String {
+    if width(s) <= max_width {
+        return s.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for g in s.graphemes(true) {
+        let w = grapheme_width(g);
+        if used + w + 1 > max_width {
+            break;
+        }
+        out.push_str(g);
+        used += w;
+    }
     out.push('…');
     out
 }
+
+/// 棒グラフ・進捗バーの桁数（stats / clock report / focus で共通）
+pub const BAR_WIDTH: usize = 20;
+
+/// n を max に対する割合で 0..=width 段に切り上げる（n が 0 より大きければ最低 1）
+pub fn scale(n: usize, max: usize, width: usize) -> usize {
+    if n == 0 || max == 0 {
+        0
+    } else {
+        (n * width).div_ceil(max).min(width)
+    }
+}
+
+/// max に対する n の割合の █ 棒（最大 BAR_WIDTH 桁）
+pub fn bar(n: usize, max: usize) -> String {
+    "█".repeat(scale(n, max, BAR_WIDTH))
+}
+
+/// 進んだ割合 ratio（0.0..=1.0）を █ と ░ で BAR_WIDTH 桁に描く
+pub fn gauge(ratio: f64) -> String {
+    let filled = ((ratio.clamp(0.0, 1.0) * BAR_WIDTH as f64) as usize).min(BAR_WIDTH);
+    format!("{}{}", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 👨‍👩‍👧 = man ZWJ woman ZWJ girl、"e\u{301}" = e + 結合アキュート
+    const FAMILY: &str = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+
+    #[test]
+    fn width_counts_display_columns() {
+        assert_eq!(width(""), 0);
+        assert_eq!(width("abc"), 3);
+        assert_eq!(width("日本語"), 6);
+        assert_eq!(width("PR レビュー"), 3 + 8);
+        assert_eq!(width(FAMILY), 2);
+        assert_eq!(width("cafe\u{301}"), 4);
+        assert_eq!(width("🍅3"), 3);
+    }
+
+    #[test]
+    fn truncate_keeps_text_that_fits() {
+        assert_eq!(truncate("abc", 3), "abc");
+        assert_eq!(truncate("日本語", 6), "日本語");
+        assert_eq!(truncate("", 0), "");
+    }
+
+    #[test]
+    fn truncate_mixed_ascii_and_cjk() {
+        assert_eq!(truncate("PR レビュー", 6), "PR レ…");
+        // 全角の途中で切れるときは1桁余らせる
+        assert_eq!(truncate("PR レビュー", 5), "PR …");
+        assert_eq!(truncate("日本語", 4), "日…");
+        assert!(width(&truncate("a日b本c語", 5)) <= 5);
+    }
+
+    #[test]
+    fn truncate_tiny_widths() {
+        assert_eq!(truncate("日本語", 0), "");
+        assert_eq!(truncate("日本語", 1), "…");
+        assert_eq!(truncate("日本語", 2), "…");
+        assert_eq!(truncate("abc", 2), "a…");
+        assert_eq!(truncate("abc", 1), "…");
+    }
+
+    #[test]
+    fn truncate_never_splits_graphemes() {
+        let s = format!("{}{}x", FAMILY, FAMILY);
+        assert_eq!(truncate(&s, 4), format!("{}…", FAMILY));
+        assert_eq!(truncate("cafe\u{301}s!", 5), "cafe\u{301}…");
+    }
+
+    #[test]
+    fn pad_fills_to_exact_width() {
+        for s in ["abc", "日本語", "PR レビュー", FAMILY, "cafe\u{301}"] {
+            for w in [0, 1, 2, 5, 12] {
+                assert_eq!(width(&pad(s, w)), w, "pad({:?}, {})", s, w);
+            }
+        }
+        assert_eq!(pad("日本", 5), "日本 ");
+        assert_eq!(pad("日本語", 2), "… ");
+    }
+
+    #[test]
+    fn bars_scale_to_the_max() {
+        assert_eq!(bar(0, 10), "");
+        assert_eq!(bar(5, 0), "");
+        assert_eq!(width(&bar(10, 10)), BAR_WIDTH);
+        assert_eq!(width(&bar(1, 1000)), 1);
+        assert_eq!(width(&bar(20, 10)), BAR_WIDTH);
+        assert_eq!(gauge(0.0), "░".repeat(BAR_WIDTH));
+        assert_eq!(gauge(0.5), format!("{}{}", "█".repeat(10), "░".repeat(10)));
+        assert_eq!(gauge(1.5), "█".repeat(BAR_WIDTH));
+    }
+}
@@ -0,0 +1,347 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use crate::config::Config;
+
+/// root_dir のローカル専用ファイル（commit しない）。.gitignore にも書く
+const LOCAL_ONLY: [&str; 5] = [".index.json", ".index.archive.json", ".lock", ".journal.ndjson", "*.tmp"];
+
+fn git(cfg: &Config, args: &[&str]) -> Result<Output> {
+    Command::new("git")
+        .arg("-C")
+        .arg(&cfg.root_dir)
+        .args(args)
+        .output()
+        .map_err(|e| anyhow!("failed to run git: {}", e))
+}
+
+/// 失敗したら stderr を含めてエラーにする
+fn git_ok(cfg: &Config, args: &[&str]) -> Result<String> {
+    let out = git(cfg, args)?;
+    if !out.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn is_repo(cfg: &Config) -> bool {
+    git(cfg, &["rev-parse", "--is-inside-work-tree"]).map(|o| o.status.success()).unwrap_or(false)
+}
+
+/// journal に記録した操作ごとに、触ったファイルだけを commit する
+/// （git.auto_commit が無効なら何もしない。失敗しても操作自体は成功扱いで警告だけ出す）
+pub fn auto_commit(cfg: &Config, message: &str, paths: &[PathBuf]) {
+    if !cfg.git.auto_commit || paths.is_empty() {
+        return;
+    }
+    if let Err(e) = commit_paths(cfg, message, paths) {
+        eprintln!("warning: git auto-commit failed: {}", e);
+    }
+}
+
+fn commit_paths(cfg: &Config, message: &str, paths: &[PathBuf]) -> Result<()> {
+    if !is_repo(cfg) {
+        return Err(anyhow!("{} is not a git repository (git init, or set git.auto_commit = false)", cfg.root_dir.display()));
+    }
+    let mut paths = paths.to_vec();
+    if let Some(p) = ensure_gitignore(cfg)? {
+        paths.push(p);
+    }
+    // 消えたパス（移動元）は git rm --cached、まだ追跡していなかったものは commit 対象から外す
+    let (present, missing): (Vec<&PathBuf>, Vec<&PathBuf>) = paths.iter().partition(|p| p.exists());
+    let present: Vec<String> = present.iter().map(|p| rel_path(cfg, p)).collect();
+    let missing: Vec<String> = missing.iter().map(|p| rel_path(cfg, p)).collect();
+
+    if !present.is_empty() {
+        let mut args = vec!["add", "-A", "--"];
+        args.extend(present.iter().map(String::as_str));
+        git_ok(cfg, &args)?;
+    }
+    if !missing.is_empty() {
+        let mut args = vec!["rm", "-q", "--cached", "--ignore-unmatch", "--"];
+        args.extend(missing.iter().map(String::as_str));
+        git_ok(cfg, &args)?;
+    }
+
+    let mut targets = present;
+    targets.extend(
+        missing
+            .into_iter()
+            .filter(|m| git_ok(cfg, &["ls-tree", "--name-only", "HEAD", "--", m]).map(|o| !o.is_empty()).unwrap_or(false)),
+    );
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    // 指定したパスに staged な変更が無ければ commit しない
+    let mut diff = vec!["diff", "--cached", "--quiet", "--"];
+    diff.extend(targets.iter().map(String::as_str));
+    if git(cfg, &diff)?.status.success() {
+        return Ok(());
+    }
+
+    let mut args = vec!["commit", "-q", "-m", message, "--"];
+    args.extend(targets.iter().map(String::as_str));
+    git_ok(cfg, &args)?;
+    Ok(())
+}
+
+/// root_dir/.gitignore に LOCAL_ONLY が無ければ足す（足したら .gitignore のパス）
+/// 既にある行や利用者が書いた行はそのまま
+fn ensure_gitignore(cfg: &Config) -> Result<Option<PathBuf>> {
+    let path = cfg.root_dir.join(".gitignore");
+    let current = std::fs::read_to_string(&path).unwrap_or_default();
+    let have: Vec<&str> = current.lines().map(str::trim).collect();
+    let missing: Vec<&str> = LOCAL_ONLY.iter().copied().filter(|p| !have.contains(p)).collect();
+    if missing.is_empty() {
+        return Ok(None);
+    }
+    let mut text = current.clone();
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    if current.is_empty() {
+        text.push_str("# todo: machine-local files\n");
+    }
+    for p in missing {
+        text.push_str(p);
+        text.push('\n');
+    }
+    std::fs::write(&path, text).map_err(|e| anyhow!("cannot write {}: {}", path.display(), e))?;
+    Ok(Some(path))
+}
+
+fn rel_path(cfg: &Config, p: &Path) -> String {
+    p.strip_prefix(&cfg.root_dir).unwrap_or(p).display().to_string()
+}
+
+/// pull --rebase → push
+/// - .gitignore にローカル専用ファイルが無ければ足す
+/// - 未 commit の変更があれば先に "sync: local changes" として commit
+/// - remote にブランチがまだ無ければ pull せずに push だけ
+/// - 衝突したら rebase を途中のまま止め、ファイルごとに frontmatter の衝突かどうかを表示する
+pub fn sync(cfg: &Config) -> Result<()> {
+    if !is_repo(cfg) {
+        return Err(anyhow!("{} is not a git repository", cfg.root_dir.display()));
+    }
+    if rebase_in_progress(cfg)? {
+        let conflicted = conflicted_files(cfg)?;
+        if !conflicted.is_empty() {
+            return Err(anyhow!(conflict_message(cfg, &conflicted)));
+        }
+        let root = cfg.root_dir.display();
+        return Err(anyhow!("a rebase is in progress: git -C {root} rebase --continue (or --abort), then todo sync"));
+    }
+
+    let remote = cfg.git.remote.as_str();
+    let branch = match &cfg.git.branch {
+        Some(b) => b.clone(),
+        None => git_ok(cfg, &["symbolic-ref", "--short", "HEAD"])?,
+    };
+
+    // ローカル専用ファイルは .gitignore で外す（除外の pathspec は ignore 済みのパスで git add がエラーになる）
+    ensure_gitignore(cfg)?;
+    git_ok(cfg, &["add", "-A", "--", "."])?;
+    if !git(cfg, &["diff", "--cached", "--quiet"])?.status.success() {
+        git_ok(cfg, &["commit", "-q", "-m", "sync: local changes"])?;
+        println!("committed local changes");
+    }
+
+    let remote_has_branch = git(cfg, &["ls-remote", "--exit-code", "--heads", remote, &branch])?.status.success();
+    if remote_has_branch {
+        let out = git(cfg, &["pull", "--rebase", "-q", remote, &branch])?;
+        if !out.status.success() {
+            let conflicted = conflicted_files(cfg)?;
+            if conflicted.is_empty() {
+                return Err(anyhow!("git pull --rebase failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+            }
+            return Err(anyhow!(conflict_message(cfg, &conflicted)));
+        }
+        println!("pulled {}/{}", remote, branch);
+    }
+
+    git_ok(cfg, &["push", "-q", remote, &format!("HEAD:{}", branch)])?;
+    println!("pushed {}/{}", remote, branch);
+    Ok(())
+}
+
+fn rebase_in_progress(cfg: &Config) -> Result<bool> {
+    for dir in ["rebase-merge", "rebase-apply"] {
+        let p = git_ok(cfg, &["rev-parse", "--git-path", dir])?;
+        if cfg.root_dir.join(p).exists() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// 衝突中のファイル（git status の XY, パス）
+fn conflicted_files(cfg: &Config) -> Result<Vec<(String, String)>> {
+    let out = git_ok(cfg, &["status", "--porcelain"])?;
+    Ok(out
+        .lines()
+        .filter_map(|l| l.split_at_checked(3).map(|(xy, p)| (xy.trim().to_string(), p.to_string())))
+        .filter(|(xy, _)| matches!(xy.as_str(), "DD" | "AU" | "UD" | "UA" | "DU" | "AA" | "UU"))
+        .collect())
+}
+
+fn conflict_message(cfg: &Config, conflicted: &[(String, String)]) -> String {
+    let mut msg = String::from("merge conflict while rebasing onto the remote:\n");
+    for (xy, f) in conflicted {
+        let detail = match std::fs::read_to_string(cfg.root_dir.join(f)) {
+            Err(_) => "deleted/moved on both sides".to_string(),
+            Ok(text) if !text.contains("<<<<<<<") => "changed on one side, deleted/moved on the other".to_string(),
+            Ok(text) => {
+                let keys = frontmatter_conflict_keys(&text);
+                if keys.is_empty() { "body".to_string() } else { format!("frontmatter: {}", keys.join(", ")) }
+            }
+        };
+        msg.push_str(&format!("  {} {}  ({})\n", xy, f, detail));
+    }
+    let root = cfg.root_dir.display();
+    msg.push_str(&format!(
+        "resolve the markers, then: git -C {root} add <files> && git -C {root} rebase --continue && todo sync\n\
+         or give up with: git -C {root} rebase --abort"
+    ));
+    msg
+}
+
+/// frontmatter（先頭の --- から次の --- まで）の中の衝突箇所に出てくるキー
+fn frontmatter_conflict_keys(text: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut lines = text.lines();
+    if lines.next() != Some("---") {
+        return keys;
+    }
+    let mut in_conflict = false;
+    for line in lines {
+        if line.starts_with("<<<<<<<") {
+            in_conflict = true;
+            continue;
+        }
+        if line.starts_with(">>>>>>>") {
+            in_conflict = false;
+            continue;
+        }
+        if line == "---" && !in_conflict {
+            break;
+        }
+        if in_conflict && !line.starts_with("=======") && !line.starts_with(' ') && !line.starts_with('-') {
+            if let Some((k, _)) = line.split_once(':') {
+                if !keys.iter().any(|x| x == k) {
+                    keys.push(k.to_string());
+                }
+            }
+        }
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GitConfig;
+    use crate::frontmatter::parse_todo_file;
+    use crate::{journal, store};
+
+    fn run(dir: &Path, args: &[&str]) -> String {
+        let out = Command::new("git").arg("-C").arg(dir).args(args).output().unwrap();
+        assert!(out.status.success(), "git {}: {}", args.join(" "), String::from_utf8_lossy(&out.stderr));
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    }
+
+    fn identify(dir: &Path) {
+        run(dir, &["config", "user.name", "test"]);
+        run(dir, &["config", "user.email", "test@example.com"]);
+    }
+
+    fn config(root: &Path) -> Config {
+        Config {
+            root_dir: root.to_path_buf(),
+            git: GitConfig { auto_commit: true, remote: "origin".to_string(), branch: Some("main".to_string()) },
+            ..Config::default()
+        }
+    }
+
+    /// journal を通して保存する（todo の更新系コマンドと同じ経路で auto-commit される）
+    fn save(cfg: &Config, name: &str, title: &str) {
+        let path = cfg.active_dir().join(name);
+        std::fs::create_dir_all(cfg.active_dir()).unwrap();
+        let text = format!(
+            "---\nid: {}\ntitle: {}\nstatus: todo\nimportance: 3\ncreated_at: 2026-01-01T09:00:00+09:00\nupdated_at: 2026-01-01T09:00:00+09:00\n---\n\n## ログ\n",
+            name, title
+        );
+        let todo = parse_todo_file(path, &text).unwrap();
+        let mut tx = journal::begin("edit", format!("edit: {}", title));
+        store::save(&mut tx, &todo).unwrap();
+        crate::finish_tx(cfg, tx).unwrap();
+    }
+
+    #[test]
+    fn sync_against_a_local_bare_repo() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+        let base = std::env::temp_dir().join(format!("todo-git-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&base).unwrap();
+        let (remote, a, b) = (base.join("remote.git"), base.join("a"), base.join("b"));
+
+        run(&base, &["init", "-q", "--bare", "-b", "main", remote.to_str().unwrap()]);
+        run(&base, &["init", "-q", "-b", "main", a.to_str().unwrap()]);
+        identify(&a);
+        run(&a, &["remote", "add", "origin", remote.to_str().unwrap()]);
+        let cfg_a = config(&a);
+
+        // add → auto-commit（.gitignore も一緒に入り、ローカル専用ファイルで汚れない）
+        save(&cfg_a, "one.md", "first");
+        assert_eq!(run(&a, &["log", "--format=%s"]), "edit: first");
+        let ignore = std::fs::read_to_string(a.join(".gitignore")).unwrap();
+        assert!(LOCAL_ONLY.iter().all(|p| ignore.lines().any(|l| l == *p)), "{}", ignore);
+        std::fs::write(a.join(".index.json"), "{}").unwrap();
+        std::fs::write(a.join(".lock"), "").unwrap();
+        std::fs::write(a.join("active").join(".one.md.1.tmp"), "").unwrap();
+        assert_eq!(run(&a, &["status", "--porcelain"]), "");
+
+        // 最初の sync は remote にブランチが無いので push だけ
+        sync(&cfg_a).unwrap();
+        assert!(!run(&remote, &["rev-parse", "main"]).is_empty());
+
+        // 2台目：clone して同じ内容が見える
+        run(&base, &["clone", "-q", remote.to_str().unwrap(), b.to_str().unwrap()]);
+        identify(&b);
+        let cfg_b = config(&b);
+        assert!(b.join("active").join("one.md").exists());
+
+        // 別ファイルの変更はそのまま rebase できる
+        save(&cfg_b, "two.md", "second");
+        sync(&cfg_b).unwrap();
+        sync(&cfg_a).unwrap();
+        assert!(a.join("active").join("two.md").exists());
+
+        // 同じ todo の title を両方で変えると衝突し、frontmatter のキーを報告する
+        save(&cfg_a, "one.md", "from a");
+        sync(&cfg_a).unwrap();
+        save(&cfg_b, "one.md", "from b");
+        let err = sync(&cfg_b).unwrap_err().to_string();
+        assert!(err.contains("active/one.md  (frontmatter: title)"), "{}", err);
+        // 解決するまでは何度呼んでも同じ案内で止まる
+        let err = sync(&cfg_b).unwrap_err().to_string();
+        assert!(err.contains("merge conflict"), "{}", err);
+        assert!(rebase_in_progress(&cfg_b).unwrap());
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn conflict_keys_only_from_frontmatter() {
+        let text = "---\nid: x\n<<<<<<< HEAD\ntitle: a\ndue: 2026-01-01\n=======\ntitle: b\n>>>>>>> theirs\n---\n<<<<<<< HEAD\nbody: a\n=======\n>>>>>>> theirs\n";
+        assert_eq!(frontmatter_conflict_keys(text), ["title", "due"]);
+        assert!(frontmatter_conflict_keys("no frontmatter").is_empty());
+    }
+}
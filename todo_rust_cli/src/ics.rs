@@ -0,0 +1,303 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::model::{Status, TodoFile};
+use crate::store;
+
+/// 書き出す iCalendar コンポーネント
+/// - todo: VTODO（タスク対応のクライアント向け、status / 完了日時も載る）
+/// - event: 期限日の VEVENT（カレンダーにだけ出すクライアント向け）
+/// - both: 両方（UID は VTODO が id、VEVENT が id + "-due"）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Component {
+    Todo,
+    Event,
+    #[default]
+    Both,
+}
+
+/// 時刻付き due の VEVENT の長さ
+const EVENT_MINUTES: i64 = 15;
+
+pub fn render(todos: &[TodoFile], component: Component) -> String {
+    let mut out = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//todo//todo CLI//EN", "CALSCALE:GREGORIAN", "X-WR-CALNAME:todo"] {
+        push_line(&mut out, line);
+    }
+    for t in todos {
+        if component != Component::Event {
+            vtodo(&mut out, t);
+        }
+        if component != Component::Todo {
+            vevent(&mut out, t);
+        }
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn vtodo(out: &mut String, t: &TodoFile) {
+    let fm = &t.fm;
+    push_line(out, "BEGIN:VTODO");
+    push_line(out, &format!("UID:{}", text(&fm.id)));
+    common(out, t);
+    if let Some(due) = fm.due.as_deref().and_then(Due::parse) {
+        push_line(out, &due.prop("DUE"));
+    }
+    let status = match fm.status {
+        Status::Todo => "NEEDS-ACTION",
+        Status::Doing | Status::Waiting => "IN-PROCESS",
+        Status::Done => "COMPLETED",
+        Status::Canceled => "CANCELLED",
+    };
+    push_line(out, &format!("STATUS:{}", status));
+    if fm.status == Status::Done {
+        if let Some(at) = fm.done_at.as_deref().and_then(utc) {
+            push_line(out, &format!("COMPLETED:{}", at));
+        }
+    }
+    push_line(out, "END:VTODO");
+}
+
+/// 期限の無いものは出さない
+fn vevent(out: &mut String, t: &TodoFile) {
+    let fm = &t.fm;
+    let Some(due) = fm.due.as_deref().and_then(Due::parse) else { return; };
+    push_line(out, "BEGIN:VEVENT");
+    push_line(out, &format!("UID:{}-due", text(&fm.id)));
+    common(out, t);
+    push_line(out, &due.prop("DTSTART"));
+    push_line(out, &due.end().prop("DTEND"));
+    push_line(out, "TRANSP:TRANSPARENT");
+    push_line(out, if fm.status == Status::Canceled { "STATUS:CANCELLED" } else { "STATUS:CONFIRMED" });
+    push_line(out, "END:VEVENT");
+}
+
+/// VTODO / VEVENT 共通のプロパティ
+fn common(out: &mut String, t: &TodoFile) {
+    let fm = &t.fm;
+    // DTSTAMP は updated_at にする（内容が変わらなければ出力も変わらない）
+    if let Some(at) = utc(&fm.updated_at) {
+        push_line(out, &format!("DTSTAMP:{}", at));
+        push_line(out, &format!("LAST-MODIFIED:{}", at));
+    }
+    if let Some(at) = utc(&fm.created_at) {
+        push_line(out, &format!("CREATED:{}", at));
+    }
+    push_line(out, &format!("SUMMARY:{}", text(&fm.title)));
+    push_line(out, &format!("PRIORITY:{}", priority(fm.importance)));
+    if !fm.tags.is_empty() {
+        let tags: Vec<String> = fm.tags.iter().map(|s| text(s)).collect();
+        push_line(out, &format!("CATEGORIES:{}", tags.join(",")));
+    }
+    let body = t.body.trim();
+    if !body.is_empty() {
+        push_line(out, &format!("DESCRIPTION:{}", text(body)));
+    }
+}
+
+/// importance 5（最重要）..1 → PRIORITY 1（最高）..9
+fn priority(importance: i32) -> i32 {
+    match importance {
+        i if i >= 5 => 1,
+        4 => 3,
+        3 => 5,
+        2 => 7,
+        _ => 9,
+    }
+}
+
+/// due：日付だけなら終日（VALUE=DATE）、RFC3339 なら UTC の日時
+enum Due {
+    Date(NaiveDate),
+    At(DateTime<Utc>),
+}
+
+impl Due {
+    fn parse(s: &str) -> Option<Due> {
+        if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Some(Due::Date(d));
+        }
+        store::parse_due_dt(s).map(|dt| Due::At(dt.with_timezone(&Utc)))
+    }
+
+    fn end(&self) -> Due {
+        match self {
+            Due::Date(d) => Due::Date(*d + Duration::days(1)),
+            Due::At(dt) => Due::At(*dt + Duration::minutes(EVENT_MINUTES)),
+        }
+    }
+
+    fn prop(&self, name: &str) -> String {
+        match self {
+            Due::Date(d) => format!("{};VALUE=DATE:{}", name, d.format("%Y%m%d")),
+            Due::At(dt) => format!("{}:{}", name, dt.format("%Y%m%dT%H%M%SZ")),
+        }
+    }
+}
+
+fn utc(rfc3339: &str) -> Option<String> {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .ok()
+        .map(|d| d.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+/// TEXT 値のエスケープ（RFC 5545 3.3.11）
+fn text(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+/// CRLF 区切り、75 オクテットを超える行は折り返す（続きの行は空白1つで始める）
+fn push_line(out: &mut String, line: &str) {
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// config の [ics] path に書き出す（更新系コマンドの後に crate::finish_tx から呼ばれる）
+/// - changed（その操作で触ったファイル）に feed の元になるものが無ければ書き直さない
+/// - done/canceled は done_at が直近 closed_days 日のものだけ（その月以降の archive しか読まない）
+///
+/// 失敗しても操作自体は成功扱いで警告だけ出す
+pub fn refresh_feed(cfg: &Config, changed: &[PathBuf]) {
+    let Some(path) = cfg.ics.path.as_ref() else { return; };
+    if path.exists() && !changed.iter().any(|p| affects_feed(cfg, p)) {
+        return;
+    }
+    let res = (|| -> anyhow::Result<()> {
+        let mut todos = store::load_active(cfg)?;
+        if cfg.ics.include_closed {
+            match cfg.ics.closed_days {
+                0 => todos.extend(store::load_all(cfg)?.into_iter().filter(|t| !t.path.starts_with(cfg.active_dir()))),
+                days => {
+                    let since = Local::now() - Duration::days(days as i64);
+                    let recent = |t: &TodoFile| {
+                        t.fm.done_at.as_deref().and_then(|s| DateTime::parse_from_rfc3339(s).ok()).is_some_and(|d| d >= since)
+                    };
+                    todos.retain(|t| t.fm.status.is_active() || recent(t));
+                    todos.extend(store::load_archive_since(cfg, since.date_naive())?.into_iter().filter(|t| recent(t)));
+                }
+            }
+        } else {
+            todos.retain(|t| t.fm.status.is_active());
+        }
+        todos.sort_by(|a, b| a.fm.id.cmp(&b.fm.id));
+        store::write_atomic(path, render(&todos, cfg.ics.component).as_bytes())
+    })();
+    if let Err(e) = res {
+        eprintln!("warning: failed to write {}: {}", path.display(), e);
+    }
+}
+
+/// feed に載りうるファイルか（active/ は常に、done/ canceled/ は include_closed のときだけ）
+fn affects_feed(cfg: &Config, path: &Path) -> bool {
+    path.starts_with(cfg.active_dir())
+        || (cfg.ics.include_closed && (path.starts_with(cfg.done_dir()) || path.starts_with(cfg.canceled_dir())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter::parse_todo_file;
+
+    fn lines(s: &str) -> Vec<&str> {
+        s.strip_suffix("\r\n").unwrap().split("\r\n").collect()
+    }
+
+    /// 折り返しを戻す（RFC 5545 3.1 の unfold）
+    fn unfold(s: &str) -> String {
+        s.replace("\r\n ", "")
+    }
+
+    #[test]
+    fn text_escapes() {
+        assert_eq!(text(r"a,b;c\d"), r"a\,b\;c\\d");
+        assert_eq!(text("l1\r\nl2\nl3\rl4"), "l1\\nl2\\nl3\\nl4");
+        assert_eq!(text("plain: 日本語"), "plain: 日本語");
+    }
+
+    #[test]
+    fn short_lines_are_not_folded() {
+        let mut out = String::new();
+        push_line(&mut out, &"x".repeat(75));
+        assert_eq!(lines(&out), [&"x".repeat(75)]);
+    }
+
+    #[test]
+    fn long_lines_fold_at_75_octets() {
+        let line = format!("SUMMARY:{}", "a".repeat(200));
+        let mut out = String::new();
+        push_line(&mut out, &line);
+        let ls = lines(&out);
+        assert!(ls.len() > 1);
+        assert_eq!(ls[0].len(), 75);
+        assert!(ls[1..].iter().all(|l| l.starts_with(' ') && l.len() <= 75));
+        assert_eq!(unfold(&out), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn folding_never_splits_a_multibyte_char() {
+        // 3 オクテットの文字は 75 の境界をまたげない
+        let line = format!("SUMMARY:{}", "請求書の確認と送付".repeat(10));
+        let mut out = String::new();
+        push_line(&mut out, &line);
+        for l in lines(&out) {
+            assert!(l.len() <= 75, "{} octets: {}", l.len(), l);
+        }
+        assert_eq!(lines(&out)[0].len(), 74);
+        assert_eq!(unfold(&out), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn importance_maps_to_priority() {
+        let got: Vec<i32> = [6, 5, 4, 3, 2, 1, 0].into_iter().map(priority).collect();
+        assert_eq!(got, [1, 1, 3, 5, 7, 9, 9]);
+    }
+
+    #[test]
+    fn render_todo_and_event() {
+        let text = "---\nid: a1\ntitle: 請求書, 月末\nstatus: doing\ndue: 2026-01-31\nimportance: 4\ncreated_at: 2026-01-01T09:00:00+09:00\nupdated_at: 2026-01-02T09:00:00+09:00\n---\n";
+        let t = parse_todo_file(PathBuf::from("a.md"), text).unwrap();
+        let out = unfold(&render(&[t], Component::Both));
+        assert!(out.starts_with("BEGIN:VCALENDAR\r\n") && out.ends_with("END:VCALENDAR\r\n"));
+        for want in [
+            "UID:a1\r\n",
+            "UID:a1-due\r\n",
+            "SUMMARY:請求書\\, 月末\r\n",
+            "PRIORITY:3\r\n",
+            "STATUS:IN-PROCESS\r\n",
+            "DUE;VALUE=DATE:20260131\r\n",
+            "DTSTART;VALUE=DATE:20260131\r\n",
+            "DTEND;VALUE=DATE:20260201\r\n",
+            "DTSTAMP:20260102T000000Z\r\n",
+        ] {
+            assert!(out.contains(want), "missing {:?}", want);
+        }
+    }
+
+    #[test]
+    fn only_feed_inputs_trigger_a_refresh() {
+        let mut cfg = Config { root_dir: PathBuf::from("/srv/todo"), ..Config::default() };
+        let done = cfg.done_dir().join("2026/01/a.md");
+        assert!(affects_feed(&cfg, &cfg.active_dir().join("a.md")));
+        assert!(!affects_feed(&cfg, &done));
+        assert!(!affects_feed(&cfg, &cfg.templates_dir().join("todo.md")));
+        cfg.ics.include_closed = true;
+        assert!(affects_feed(&cfg, &done));
+    }
+}
@@ -0,0 +1,672 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use clap::ValueEnum;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::model::{FrontMatter, Status, TodoFile};
+use crate::journal::{self, Tx};
+use crate::{activity, store};
+
+/// `todo import --from` の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// todo.txt（1行1件）
+    Todotxt,
+    /// `task export` の JSON（配列 or 1行1件）
+    Taskwarrior,
+    /// `gh issue list --json ...` や REST API の issues の JSON 配列
+    GithubJson,
+}
+
+impl ImportFormat {
+    fn name(self) -> &'static str {
+        match self {
+            ImportFormat::Todotxt => "todotxt",
+            ImportFormat::Taskwarrior => "taskwarrior",
+            ImportFormat::GithubJson => "github-json",
+        }
+    }
+}
+
+/// 取り込む1件（ファイルにする前）
+struct Draft {
+    /// 元データのどこか（"line 3"、"uuid ..."、"#12"）
+    source: String,
+    title: String,
+    status: Status,
+    due: Option<String>,
+    tags: Vec<String>,
+    importance: i32,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    done_at: Option<String>,
+    /// "## メモ" に入れる元データの補足（注釈、URL、本文など）
+    note: Option<String>,
+}
+
+impl Draft {
+    fn new(source: String, title: String) -> Self {
+        Draft {
+            source,
+            title,
+            status: Status::Todo,
+            due: None,
+            tags: Vec::new(),
+            importance: 3,
+            created_at: None,
+            updated_at: None,
+            done_at: None,
+            note: None,
+        }
+    }
+}
+
+/// 取り込めなかった1件
+struct Skip {
+    source: String,
+    reason: String,
+}
+
+/// file ("-" なら stdin) を読み、1件ずつ add と同じ命名で active/ に
+/// （完了済みは done/YYYY/MM・canceled/YYYY/MM に直接）作る
+/// - 同じタイトル + created_at の todo が既にあれば取り込み済みとして飛ばす
+///   元データに作成日が無いものは、タイトル + 取り込み元（ログの `imported from todotxt (line 3)`）で見る
+/// - 全体で1操作として journal に記録する（todo undo でまとめて取り消せる）
+pub fn run(cfg: &Config, format: ImportFormat, file: &Path, dry_run: bool) -> Result<()> {
+    let text = if file == Path::new("-") {
+        let mut s = String::new();
+        std::io::stdin().read_to_string(&mut s)?;
+        s
+    } else {
+        std::fs::read_to_string(file).map_err(|e| anyhow!("cannot read {}: {}", file.display(), e))?
+    };
+
+    let parsed = match format {
+        ImportFormat::Todotxt => parse_todotxt(&text),
+        ImportFormat::Taskwarrior => parse_taskwarrior(&text)?,
+        ImportFormat::GithubJson => parse_github(&text)?,
+    };
+
+    let _lock = if dry_run { None } else { Some(store::lock_root(cfg)?) };
+    let existing = store::load_all(cfg)?;
+    let mut ids: HashSet<String> = existing.iter().map(|t| t.fm.id.clone()).collect();
+    let mut seen: HashSet<(String, String)> = existing.iter().map(|t| (t.fm.title.clone(), t.fm.created_at.clone())).collect();
+    let mut seen_sources = imported_sources(&existing);
+
+    let mut tx = journal::begin("import", format!("import: {} ({})", file.display(), format.name()));
+    let mut created = 0;
+    let mut skipped: Vec<Skip> = Vec::new();
+
+    for item in parsed {
+        let draft = match item {
+            Ok(d) => d,
+            Err(s) => {
+                skipped.push(s);
+                continue;
+            }
+        };
+        let source = draft.source.clone();
+        // 同じファイル内の重複も飛ばす
+        let fresh = match draft.created_at.as_ref() {
+            Some(c) => seen.insert((draft.title.clone(), c.clone())),
+            None => seen_sources.insert((draft.title.clone(), source_key(format, &source))),
+        };
+        if !fresh {
+            skipped.push(Skip { source, reason: format!("already imported: {}", draft.title) });
+            continue;
+        }
+        match create(cfg, &mut tx, draft, format, &mut ids, dry_run) {
+            Ok(path) => {
+                created += 1;
+                let verb = if dry_run { "would create" } else { "created" };
+                println!("{}: {}", verb, path.display());
+            }
+            Err(e) => skipped.push(Skip { source, reason: e.to_string().lines().collect::<Vec<_>>().join(" ") }),
+        }
+    }
+    if !dry_run {
+        crate::finish_tx(cfg, tx)?;
+    }
+
+    for s in &skipped {
+        println!("skipped {}: {}", s.source, s.reason);
+    }
+    let verb = if dry_run { "would import" } else { "imported" };
+    println!("{} {} todo(s), skipped {}", verb, created, skipped.len());
+    Ok(())
+}
+
+fn create(cfg: &Config, tx: &mut Tx, d: Draft, format: ImportFormat, ids: &mut HashSet<String>, dry_run: bool) -> Result<std::path::PathBuf> {
+    let now = store::now_jst_rfc3339();
+    let id = new_id(ids);
+    ids.insert(id.clone());
+
+    let done_at = match d.status {
+        Status::Done | Status::Canceled => Some(d.done_at.clone().unwrap_or_else(|| now.clone())),
+        _ => None,
+    };
+    let slug = slug::slugify(&d.title);
+    let path = match done_at.as_deref() {
+        Some(at) => store::new_archive_path(cfg, &d.status, at, Some(&slug))?,
+        None => store::new_active_path(cfg, Some(&slug)),
+    };
+
+    let created_at = d.created_at.clone().unwrap_or_else(|| now.clone());
+    let mut body = crate::new_body(cfg, &id, &d.title, &created_at)?;
+    if let Some(note) = d.note.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        insert_memo(&mut body, note);
+    }
+
+    let fm = FrontMatter {
+        id,
+        title: d.title,
+        status: d.status,
+        due: d.due,
+        tags: d.tags,
+        importance: d.importance,
+        created_at,
+        updated_at: d.updated_at.unwrap_or(now),
+        done_at,
+        restored_from: None,
+        repeat: None,
+        blocked_by: Vec::new(),
+        clock: Vec::new(),
+        pomodoros: 0,
+    };
+    let mut todo = TodoFile { path: path.clone(), fm, body };
+    let at = activity::stamp();
+    todo.append_log_line(&at, &format!("imported from {}", source_key(format, &d.source)));
+
+    if dry_run {
+        crate::validate::ensure_valid(&todo, None)?;
+        return Ok(path);
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    store::save(tx, &todo)?;
+    Ok(path)
+}
+
+/// ログに残す取り込み元：`todotxt (line 3)` / `github-json (#12)`
+fn source_key(format: ImportFormat, source: &str) -> String {
+    format!("{} ({})", format.name(), source)
+}
+
+/// 既存の todo のうち取り込みで作ったものの (タイトル, 取り込み元)
+fn imported_sources(existing: &[TodoFile]) -> HashSet<(String, String)> {
+    existing
+        .iter()
+        .flat_map(|t| {
+            activity::parse(&t.body)
+                .into_iter()
+                .filter(|e| e.action == "import")
+                .filter_map(|e| e.text.strip_prefix("from ").map(str::to_string))
+                .map(|src| (t.fm.title.clone(), src))
+        })
+        .collect()
+}
+
+/// "## メモ" の直後に入れる（見出しが無ければ先頭に作る）
+fn insert_memo(body: &mut String, note: &str) {
+    let text = format!("{}\n", note);
+    if let Some(pos) = body.find("## メモ\n") {
+        body.insert_str(pos + "## メモ\n".len(), &text);
+    } else {
+        body.insert_str(0, &format!("\n## メモ\n{}", text));
+    }
+}
+
+fn new_id(ids: &HashSet<String>) -> String {
+    loop {
+        let id = Local::now().to_rfc3339_opts(SecondsFormat::Nanos, false);
+        if !ids.contains(&id) {
+            return id;
+        }
+    }
+}
+
+/// 日付だけのものはローカルの 0 時として RFC3339 に
+fn date_rfc3339(d: NaiveDate) -> Option<String> {
+    let dt = Local.from_local_datetime(&d.and_hms_opt(0, 0, 0)?).earliest()?;
+    Some(dt.to_rfc3339())
+}
+
+/// UTC の日時 → due（ローカルで 0 時ちょうどなら日付だけ）
+fn due_from_utc(dt: DateTime<Utc>) -> String {
+    let local = dt.with_timezone(&Local);
+    if local.format("%H:%M:%S").to_string() == "00:00:00" {
+        local.format("%Y-%m-%d").to_string()
+    } else {
+        local.to_rfc3339()
+    }
+}
+
+// ---- todo.txt ----
+
+/// `x 2026-01-02 2026-01-01 (A) title +project @context due:2026-01-10`
+/// - (A)..(E) → importance 5..1（F 以降も 1、無ければ 3）。完了行の pri:A や日付の後ろの (A) も見る
+/// - +project / @context → tags（記号は外す）
+/// - due: → due、t: rec: などその他の key:value はメモに残す
+fn parse_todotxt(text: &str) -> Vec<Result<Draft, Skip>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| parse_todotxt_line(l.trim(), format!("line {}", i + 1)))
+        .collect()
+}
+
+fn parse_todotxt_line(line: &str, source: String) -> Result<Draft, Skip> {
+    let skip = |reason: String| Skip { source: source.clone(), reason };
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+    let mut status = Status::Todo;
+    let mut done_date = None;
+    let mut created = None;
+    let mut priority: Option<char> = None;
+
+    if words.first() == Some(&"x") {
+        status = Status::Done;
+        words.remove(0);
+        if let Some(d) = words.first().and_then(|w| NaiveDate::parse_from_str(w, "%Y-%m-%d").ok()) {
+            done_date = Some(d);
+            words.remove(0);
+        }
+    } else if let Some(p) = words.first().and_then(|w| todotxt_priority(w)) {
+        priority = Some(p);
+        words.remove(0);
+    }
+    if let Some(d) = words.first().and_then(|w| NaiveDate::parse_from_str(w, "%Y-%m-%d").ok()) {
+        created = Some(d);
+        words.remove(0);
+    }
+    // 完了にしたときに (A) を残すツールもある（`x 2026-01-02 2026-01-01 (A) title`）
+    if status == Status::Done && priority.is_none() {
+        if let Some(p) = words.first().and_then(|w| todotxt_priority(w)) {
+            priority = Some(p);
+            words.remove(0);
+        }
+    }
+
+    let mut title = Vec::new();
+    let mut tags = Vec::new();
+    let mut due = None;
+    let mut extra = Vec::new();
+    for w in words {
+        if let Some(t) = w.strip_prefix('+').or_else(|| w.strip_prefix('@')).filter(|t| !t.is_empty()) {
+            if !tags.iter().any(|x| x == t) {
+                tags.push(t.to_string());
+            }
+            continue;
+        }
+        match w.split_once(':') {
+            Some(("due", v)) => {
+                let d = NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| skip(format!("invalid due: {}", v)))?;
+                due = Some(d.format("%Y-%m-%d").to_string());
+            }
+            Some(("pri", v)) if v.len() == 1 => priority = v.chars().next().filter(char::is_ascii_uppercase),
+            Some((k, v)) if !v.is_empty() && !k.is_empty() && k.chars().all(|c| c.is_ascii_alphanumeric()) && !v.starts_with('/') => {
+                extra.push(w.to_string())
+            }
+            _ => title.push(w),
+        }
+    }
+
+    let title = title.join(" ");
+    if title.is_empty() {
+        return Err(skip("empty title".to_string()));
+    }
+    let mut d = Draft::new(source, title);
+    d.status = status;
+    d.tags = tags;
+    d.due = due;
+    d.importance = match priority {
+        Some('A') => 5,
+        Some('B') => 4,
+        Some('C') => 3,
+        Some('D') => 2,
+        Some(_) => 1,
+        None => 3,
+    };
+    d.created_at = created.and_then(date_rfc3339);
+    d.done_at = done_date.and_then(date_rfc3339);
+    if !extra.is_empty() {
+        d.note = Some(format!("todo.txt: {}", extra.join(" ")));
+    }
+    Ok(d)
+}
+
+fn todotxt_priority(w: &str) -> Option<char> {
+    let b = w.as_bytes();
+    if b.len() == 3 && b[0] == b'(' && b[2] == b')' && b[1].is_ascii_uppercase() {
+        Some(b[1] as char)
+    } else {
+        None
+    }
+}
+
+// ---- Taskwarrior ----
+
+/// `task export` の1件
+/// - status: pending → todo（start があれば doing）、waiting → waiting、completed → done、deleted → canceled
+///   （recurring は繰り返しの雛形なので飛ばす）
+/// - priority: H → 5、M → 4、L → 2、無し → 3
+/// - project / tags → tags、annotations → メモ
+fn parse_taskwarrior(text: &str) -> Result<Vec<Result<Draft, Skip>>> {
+    let values = json_records(text)?;
+    Ok(values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let source = v
+                .get("uuid")
+                .and_then(Value::as_str)
+                .map(|u| format!("uuid {}", u))
+                .unwrap_or_else(|| format!("record {}", i + 1));
+            parse_taskwarrior_task(v, source)
+        })
+        .collect())
+}
+
+fn parse_taskwarrior_task(v: &Value, source: String) -> Result<Draft, Skip> {
+    let skip = |reason: String| Skip { source: source.clone(), reason };
+    let title = str_field(v, &["description"]).filter(|s| !s.trim().is_empty()).ok_or_else(|| skip("no description".to_string()))?;
+
+    let status = match str_field(v, &["status"]).as_deref() {
+        Some("pending") | None if v.get("start").is_some() => Status::Doing,
+        Some("pending") | None => Status::Todo,
+        Some("waiting") => Status::Waiting,
+        Some("completed") => Status::Done,
+        Some("deleted") => Status::Canceled,
+        Some("recurring") => return Err(skip("recurring template (its pending instances are imported)".to_string())),
+        Some(s) => return Err(skip(format!("unknown status: {}", s))),
+    };
+
+    let mut d = Draft::new(source.clone(), title.trim().to_string());
+    d.status = status;
+    d.importance = match str_field(v, &["priority"]).as_deref() {
+        Some("H") => 5,
+        Some("M") => 4,
+        Some("L") => 2,
+        _ => 3,
+    };
+    if let Some(p) = str_field(v, &["project"]) {
+        d.tags.push(p);
+    }
+    for t in v.get("tags").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+        if !d.tags.iter().any(|x| x == t) {
+            d.tags.push(t.to_string());
+        }
+    }
+    if let Some(s) = str_field(v, &["due"]) {
+        d.due = Some(due_from_utc(tw_date(&s).ok_or_else(|| skip(format!("invalid due: {}", s)))?));
+    }
+    d.created_at = str_field(v, &["entry"]).and_then(|s| tw_date(&s)).map(|t| t.with_timezone(&Local).to_rfc3339());
+    d.updated_at = str_field(v, &["modified"]).and_then(|s| tw_date(&s)).map(|t| t.with_timezone(&Local).to_rfc3339());
+    d.done_at = str_field(v, &["end"]).and_then(|s| tw_date(&s)).map(|t| t.with_timezone(&Local).to_rfc3339());
+
+    let notes: Vec<String> = v
+        .get("annotations")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|a| {
+            let text = a.get("description")?.as_str()?;
+            let date = a.get("entry").and_then(Value::as_str).and_then(tw_date);
+            Some(match date {
+                Some(t) => format!("- {}: {}", t.with_timezone(&Local).format("%Y-%m-%d"), text),
+                None => format!("- {}", text),
+            })
+        })
+        .collect();
+    if !notes.is_empty() {
+        d.note = Some(notes.join("\n"));
+    }
+    Ok(d)
+}
+
+/// 20260110T120000Z
+fn tw_date(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%SZ")
+        .ok()
+        .map(|n| n.and_utc())
+        .or_else(|| DateTime::parse_from_rfc3339(s).ok().map(|d| d.with_timezone(&Utc)))
+}
+
+// ---- GitHub issues ----
+
+/// gh（camelCase、state は OPEN/CLOSED）と REST API（snake_case）のどちらのキーでも読む
+/// - closed は stateReason が NOT_PLANNED なら canceled、それ以外は done
+/// - labels → tags。"P0".."P4" / "priority: high" などのラベルは importance にも反映
+/// - milestone の期限 → due、URL と本文 → メモ。pull request は飛ばす
+fn parse_github(text: &str) -> Result<Vec<Result<Draft, Skip>>> {
+    let values = json_records(text)?;
+    Ok(values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let source = v
+                .get("number")
+                .and_then(Value::as_u64)
+                .map(|n| format!("#{}", n))
+                .unwrap_or_else(|| format!("record {}", i + 1));
+            parse_github_issue(v, source)
+        })
+        .collect())
+}
+
+fn parse_github_issue(v: &Value, source: String) -> Result<Draft, Skip> {
+    let skip = |reason: String| Skip { source: source.clone(), reason };
+    if v.get("pull_request").is_some() || v.get("isPullRequest").and_then(Value::as_bool) == Some(true) {
+        return Err(skip("pull request".to_string()));
+    }
+    let title = str_field(v, &["title"]).filter(|s| !s.trim().is_empty()).ok_or_else(|| skip("no title".to_string()))?;
+
+    let state = str_field(v, &["state"]).unwrap_or_default().to_lowercase();
+    let reason = str_field(v, &["stateReason", "state_reason"]).unwrap_or_default().to_lowercase();
+    let status = match (state.as_str(), reason.as_str()) {
+        ("closed", "not_planned") => Status::Canceled,
+        ("closed", _) => Status::Done,
+        ("open", _) | ("", _) => Status::Todo,
+        (s, _) => return Err(skip(format!("unknown state: {}", s))),
+    };
+
+    let mut d = Draft::new(source.clone(), title.trim().to_string());
+    d.status = status;
+    for label in v.get("labels").and_then(Value::as_array).into_iter().flatten() {
+        let Some(name) = label.get("name").and_then(Value::as_str).or_else(|| label.as_str()) else { continue; };
+        if let Some(imp) = label_importance(name) {
+            d.importance = imp;
+        }
+        if !d.tags.iter().any(|x| x == name) {
+            d.tags.push(name.to_string());
+        }
+    }
+
+    let milestone_due = v.get("milestone").and_then(|m| str_field(m, &["dueOn", "due_on"]));
+    if let Some(s) = milestone_due {
+        let dt = DateTime::parse_from_rfc3339(&s).map_err(|_| skip(format!("invalid milestone due: {}", s)))?;
+        // GitHub の期限は日付だけの意味（UTC の 0 時や 7 時などで返る）
+        d.due = Some(dt.format("%Y-%m-%d").to_string());
+    }
+    let local = |s: String| DateTime::parse_from_rfc3339(&s).ok().map(|t| t.with_timezone(&Local).to_rfc3339());
+    d.created_at = str_field(v, &["createdAt", "created_at"]).and_then(local);
+    d.updated_at = str_field(v, &["updatedAt", "updated_at"]).and_then(local);
+    d.done_at = str_field(v, &["closedAt", "closed_at"]).and_then(local);
+
+    let mut note = Vec::new();
+    if let Some(url) = str_field(v, &["url", "html_url"]) {
+        note.push(url);
+    }
+    if let Some(body) = str_field(v, &["body"]).filter(|b| !b.trim().is_empty()) {
+        note.push(String::new());
+        note.push(body.replace("\r\n", "\n").trim().to_string());
+    }
+    if !note.is_empty() {
+        d.note = Some(note.join("\n"));
+    }
+    Ok(d)
+}
+
+/// P0..P4 / priority: critical|high|medium|low → importance
+fn label_importance(name: &str) -> Option<i32> {
+    let n = name.to_lowercase();
+    let n = n.trim_start_matches("priority").trim_start_matches([':', '/', '-', ' ']).trim();
+    match n {
+        "p0" | "critical" | "urgent" => Some(5),
+        "p1" | "high" => Some(4),
+        "p2" | "medium" => Some(3),
+        "p3" | "low" => Some(2),
+        "p4" => Some(1),
+        _ => None,
+    }
+}
+
+// ---- 共通 ----
+
+/// JSON 配列、または1行1件（NDJSON）
+fn json_records(text: &str) -> Result<Vec<Value>> {
+    let text = text.trim();
+    if text.starts_with('[') {
+        return match serde_json::from_str(text)? {
+            Value::Array(a) => Ok(a),
+            _ => Err(anyhow!("expected a JSON array")),
+        };
+    }
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .map(|(i, l)| serde_json::from_str(l.trim_end_matches(',')).map_err(|e| anyhow!("invalid JSON on line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// keys のうち最初にある文字列
+fn str_field(v: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|k| v.get(*k).and_then(Value::as_str)).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn todotxt(line: &str) -> Draft {
+        parse_todotxt_line(line, "line 1".to_string()).unwrap_or_else(|s| panic!("{}", s.reason))
+    }
+
+    fn date(d: &Option<String>) -> String {
+        d.as_deref().and_then(|s| DateTime::parse_from_rfc3339(s).ok()).map(|t| t.with_timezone(&Local).format("%Y-%m-%d").to_string()).unwrap_or_default()
+    }
+
+    #[test]
+    fn todotxt_open_line() {
+        let d = todotxt("(B) 2026-01-01 Call mom +family @phone due:2026-01-10 t:2026-01-05");
+        assert_eq!(d.title, "Call mom");
+        assert_eq!(d.status, Status::Todo);
+        assert_eq!(d.importance, 4);
+        assert_eq!(d.tags, ["family", "phone"]);
+        assert_eq!(d.due.as_deref(), Some("2026-01-10"));
+        assert_eq!(date(&d.created_at), "2026-01-01");
+        assert_eq!(d.note.as_deref(), Some("todo.txt: t:2026-01-05"));
+    }
+
+    #[test]
+    fn todotxt_completed_lines() {
+        let d = todotxt("x 2026-01-02 2026-01-01 done thing pri:A");
+        assert_eq!((d.title.as_str(), d.status.clone(), d.importance), ("done thing", Status::Done, 5));
+        assert_eq!(date(&d.done_at), "2026-01-02");
+        assert_eq!(date(&d.created_at), "2026-01-01");
+
+        // 日付の後ろに残った (A) はタイトルに入れない
+        let d = todotxt("x 2026-01-02 2026-01-01 (A) done thing");
+        assert_eq!((d.title.as_str(), d.importance), ("done thing", 5));
+        let d = todotxt("x (C) no dates");
+        assert_eq!((d.title.as_str(), d.importance), ("no dates", 3));
+    }
+
+    #[test]
+    fn todotxt_keeps_urls_and_stray_parens_in_title() {
+        assert_eq!(todotxt("read https://example.com/a").title, "read https://example.com/a");
+        assert_eq!(todotxt("2026-01-01 (A) not a priority").title, "(A) not a priority");
+        assert_eq!(todotxt("(a) lowercase").title, "(a) lowercase");
+    }
+
+    #[test]
+    fn todotxt_rejects_bad_lines() {
+        assert!(parse_todotxt_line("+tag @ctx", "line 1".to_string()).is_err());
+        assert!(parse_todotxt_line("thing due:tomorrow", "line 1".to_string()).is_err());
+    }
+
+    #[test]
+    fn taskwarrior_statuses_and_fields() {
+        let v = json!({
+            "uuid": "u1", "description": "Fix bug", "status": "pending", "start": "20260101T090000Z",
+            "priority": "H", "project": "work", "tags": ["bug", "work"],
+            "entry": "20260101T000000Z", "due": "20260110T150000Z",
+            "annotations": [{"entry": "20260102T000000Z", "description": "see log"}]
+        });
+        let d = parse_taskwarrior_task(&v, "uuid u1".to_string()).unwrap_or_else(|s| panic!("{}", s.reason));
+        assert_eq!(d.status, Status::Doing);
+        assert_eq!(d.importance, 5);
+        assert_eq!(d.tags, ["work", "bug"]);
+        assert!(d.due.is_some());
+        assert!(d.note.as_deref().unwrap().ends_with(": see log"));
+
+        let status = |s: &str| parse_taskwarrior_task(&json!({"description": "x", "status": s}), String::new()).map(|d| d.status);
+        assert_eq!(status("pending").ok(), Some(Status::Todo));
+        assert_eq!(status("waiting").ok(), Some(Status::Waiting));
+        assert_eq!(status("completed").ok(), Some(Status::Done));
+        assert_eq!(status("deleted").ok(), Some(Status::Canceled));
+        assert!(status("recurring").is_err());
+        assert!(parse_taskwarrior_task(&json!({"status": "pending"}), String::new()).is_err());
+    }
+
+    #[test]
+    fn github_issues_gh_and_rest_keys() {
+        let gh = json!({
+            "number": 12, "title": "Crash on start", "state": "CLOSED", "stateReason": "NOT_PLANNED",
+            "labels": [{"name": "bug"}, {"name": "priority: high"}],
+            "milestone": {"dueOn": "2026-02-01T08:00:00Z"},
+            "url": "https://github.com/o/r/issues/12", "body": "steps\r\nto repro",
+            "createdAt": "2026-01-01T00:00:00Z", "closedAt": "2026-01-03T00:00:00Z"
+        });
+        let d = parse_github_issue(&gh, "#12".to_string()).unwrap_or_else(|s| panic!("{}", s.reason));
+        assert_eq!(d.status, Status::Canceled);
+        assert_eq!(d.importance, 4);
+        assert_eq!(d.tags, ["bug", "priority: high"]);
+        assert_eq!(d.due.as_deref(), Some("2026-02-01"));
+        assert_eq!(d.note.as_deref(), Some("https://github.com/o/r/issues/12\n\nsteps\nto repro"));
+        assert!(d.done_at.is_some());
+
+        let rest = json!({"number": 3, "title": "Docs", "state": "closed", "state_reason": "completed", "labels": ["P0"]});
+        let d = parse_github_issue(&rest, "#3".to_string()).unwrap_or_else(|s| panic!("{}", s.reason));
+        assert_eq!((d.status, d.importance), (Status::Done, 5));
+
+        let pr = json!({"number": 4, "title": "PR", "state": "open", "pull_request": {}});
+        assert!(parse_github_issue(&pr, "#4".to_string()).is_err());
+    }
+
+    #[test]
+    fn json_array_or_ndjson() {
+        assert_eq!(json_records("[{\"a\":1},{\"a\":2}]").unwrap().len(), 2);
+        assert_eq!(json_records("{\"a\":1}\n\n{\"a\":2},\n").unwrap().len(), 2);
+        assert!(json_records("{\"a\":1}\nnope").is_err());
+        assert!(json_records("[1").is_err());
+    }
+
+    #[test]
+    fn reimport_without_created_date_is_recognized() {
+        let mut t = crate::frontmatter::parse_todo_file(
+            std::path::PathBuf::from("t.md"),
+            "---\nid: x\ntitle: Call mom\nstatus: todo\nimportance: 3\ncreated_at: 2026-10-17T10:00:00+09:00\nupdated_at: 2026-10-17T10:00:00+09:00\n---\n## ログ\n",
+        )
+        .unwrap();
+        t.append_log_line("2026-10-17T10:00:00+09:00", &format!("imported from {}", source_key(ImportFormat::Todotxt, "line 3")));
+        let seen = imported_sources(&[t]);
+        assert!(seen.contains(&("Call mom".to_string(), source_key(ImportFormat::Todotxt, "line 3"))));
+        assert!(!seen.contains(&("Call mom".to_string(), source_key(ImportFormat::Todotxt, "line 4"))));
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::config::Config;
+use crate::frontmatter::parse_todo_file;
+use crate::model::{FrontMatter, TodoFile};
+use crate::store;
+
+/// キャッシュ形式のバージョン。FrontMatter のフィールドを変えたら上げる。
+const INDEX_VERSION: u32 = 5;
+
+/// どのキャッシュファイルに入れるか
+/// active/ は status-line などから数秒おきに読まれるので、増え続ける done/ canceled/ とは分けて小さく保つ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Area {
+    /// root_dir/.index.json（active/）
+    Active,
+    /// root_dir/.index.archive.json（done/ と canceled/）
+    Archive,
+}
+
+/// パース結果のキャッシュ（Area ごとに1ファイル）
+/// - キーは絶対パス、mtime + size が一致すればキャッシュを使う
+/// - 一致しなければ読み直して差し替え、消えたファイルは走査時に落とす
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Index {
+    version: u32,
+    entries: HashMap<PathBuf, Entry>,
+
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    size: u64,
+    fm: FrontMatter,
+    body: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    size: u64,
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let meta = std::fs::metadata(path).ok()?;
+    let mtime = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(Stamp { mtime_secs: mtime.as_secs(), mtime_nanos: mtime.subsec_nanos(), size: meta.len() })
+}
+
+impl Index {
+    /// 読めない・バージョン違いの場合は空の index から始める
+    pub fn load(cfg: &Config, area: Area) -> Self {
+        let path = match area {
+            Area::Active => cfg.index_path(),
+            Area::Archive => cfg.archive_index_path(),
+        };
+        let mut idx = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<Index>(&s).ok())
+            .filter(|i| i.version == INDEX_VERSION)
+            .unwrap_or_default();
+        idx.version = INDEX_VERSION;
+        idx.path = path;
+        idx
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 変更があったときだけ書き出す（tmp に書いて rename）
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        store::write_atomic(&self.path, &serde_json::to_vec(self)?)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// キャッシュが新しければそれを、古ければ読み直して返す
+    pub fn get_or_parse(&mut self, path: &Path) -> Result<TodoFile> {
+        let st = stamp(path);
+
+        if let (Some(st), Some(e)) = (st, self.entries.get(path)) {
+            if e.mtime_secs == st.mtime_secs && e.mtime_nanos == st.mtime_nanos && e.size == st.size {
+                return Ok(TodoFile { path: path.to_path_buf(), fm: e.fm.clone(), body: e.body.clone() });
+            }
+        }
+
+        let text = std::fs::read_to_string(path)?;
+        let todo = parse_todo_file(path.to_path_buf(), &text)?;
+
+        if let Some(st) = st {
+            self.entries.insert(
+                path.to_path_buf(),
+                Entry {
+                    mtime_secs: st.mtime_secs,
+                    mtime_nanos: st.mtime_nanos,
+                    size: st.size,
+                    fm: todo.fm.clone(),
+                    body: todo.body.clone(),
+                },
+            );
+            self.dirty = true;
+        }
+        Ok(todo)
+    }
+
+    /// dir 配下で今回見つからなかったエントリを削除
+    pub fn retain_seen(&mut self, dir: &Path, seen: &HashSet<PathBuf>) {
+        let before = self.entries.len();
+        self.entries.retain(|p, _| !p.starts_with(dir) || seen.contains(p));
+        if self.entries.len() != before {
+            self.dirty = true;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.dirty = true;
+    }
+}
@@ -0,0 +1,365 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::store;
+
+/// root_dir/.journal.ndjson の1行 = 1操作
+/// 変更したファイルごとに前後の内容とパスを持つ（undo で書き戻す）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub seq: u64,
+    pub at: String,
+    pub op: String,
+    pub summary: String,
+    /// undo の記録なら取り消した操作の seq
+    #[serde(default)]
+    pub undoes: Option<u64>,
+    pub changes: Vec<FileChange>,
+}
+
+/// 1ファイル分の変更（before_path=None は新規作成、after_path=None は削除）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub before_path: Option<PathBuf>,
+    pub before: Option<String>,
+    pub after_path: Option<PathBuf>,
+    pub after: Option<String>,
+}
+
+/// 操作の記録。store の書き込み・移動には同じ Tx を渡し、触ったファイルをここに集める
+/// （after_path は「今どこにあるか」を追跡する。finish() せずに drop すると記録は捨てる）
+pub struct Tx {
+    op: String,
+    summary: String,
+    undoes: Option<u64>,
+    changes: Vec<FileChange>,
+}
+
+pub fn begin(op: &str, summary: impl Into<String>) -> Tx {
+    Tx { op: op.to_string(), summary: summary.into(), undoes: None, changes: Vec::new() }
+}
+
+/// この件数ごとに古い記録を journal_keep_last 件まで切り詰める
+const ROTATE_EVERY: u64 = 50;
+
+impl Tx {
+    /// path を書き換える直前に呼ぶ
+    pub fn note_write(&mut self, path: &Path) {
+        if self.changes.iter().any(|c| c.after_path.as_deref() == Some(path)) {
+            return;
+        }
+        let before = std::fs::read_to_string(path).ok();
+        self.changes.push(FileChange {
+            before_path: before.as_ref().map(|_| path.to_path_buf()),
+            before,
+            after_path: Some(path.to_path_buf()),
+            after: None,
+        });
+    }
+
+    /// src → dest の rename 直前に呼ぶ
+    pub fn note_rename(&mut self, src: &Path, dest: &Path) {
+        if let Some(c) = self.changes.iter_mut().find(|c| c.after_path.as_deref() == Some(src)) {
+            c.after_path = Some(dest.to_path_buf());
+            return;
+        }
+        self.changes.push(FileChange {
+            before_path: Some(src.to_path_buf()),
+            before: std::fs::read_to_string(src).ok(),
+            after_path: Some(dest.to_path_buf()),
+            after: None,
+        });
+    }
+
+    /// 変更があれば journal に追記して記録を返す（変更なしなら何も書かない）
+    /// 操作が途中で失敗した場合も、それまでの変更は記録しておく（undo できるように）
+    /// auto-commit や ics の書き出しは呼び出し側（crate::finish_tx）で行う
+    pub fn finish(self, cfg: &Config) -> Result<Option<Record>> {
+        let changes: Vec<FileChange> = self
+            .changes
+            .into_iter()
+            .map(|mut c| {
+                c.after = c.after_path.as_deref().and_then(|p| std::fs::read_to_string(p).ok());
+                if c.after.is_none() {
+                    c.after_path = None;
+                }
+                c
+            })
+            .filter(|c| c.before_path != c.after_path || c.before != c.after)
+            .collect();
+        if changes.is_empty() {
+            return Ok(None);
+        }
+
+        let path = cfg.journal_path();
+        let seq = last_seq(&path)? + 1;
+        let rec = Record {
+            seq,
+            at: store::now_jst_rfc3339(),
+            op: self.op,
+            summary: self.summary,
+            undoes: self.undoes,
+            changes,
+        };
+        let mut f = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(f, "{}", serde_json::to_string(&rec)?)?;
+        f.sync_all()?;
+
+        if cfg.journal_keep_last > 0 && seq % ROTATE_EVERY == 0 {
+            rotate(cfg, cfg.journal_keep_last)?;
+        }
+        Ok(Some(rec))
+    }
+}
+
+impl Record {
+    /// 変更前後のパス（重複なし、記録順）
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for c in &self.changes {
+            for p in [&c.before_path, &c.after_path].into_iter().flatten() {
+                if !paths.contains(p) {
+                    paths.push(p.clone());
+                }
+            }
+        }
+        paths
+    }
+}
+
+/// 最後の記録の seq（無ければ 0）
+/// 1行に変更前後の全文が入るので、全体はパースせず末尾の1行だけを読む
+fn last_seq(path: &Path) -> Result<u64> {
+    let mut f = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    let mut pos = f.metadata()?.len();
+    let mut buf: Vec<u8> = Vec::new();
+    let line = loop {
+        let end = buf.iter().rposition(|b| !b.is_ascii_whitespace()).map(|i| i + 1).unwrap_or(0);
+        if let Some(i) = buf[..end].iter().rposition(|b| *b == b'\n') {
+            break &buf[i + 1..end];
+        }
+        if pos == 0 {
+            break &buf[..end];
+        }
+        // 読んだ分と同じだけ遡る（長い行でも読み直しは合計で線形）
+        let n = (buf.len() as u64).max(8192).min(pos);
+        pos -= n;
+        let mut chunk = vec![0; n as usize];
+        f.seek(SeekFrom::Start(pos))?;
+        f.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    };
+    if line.is_empty() {
+        return Ok(0);
+    }
+
+    #[derive(Deserialize)]
+    struct Tail {
+        seq: u64,
+    }
+    let tail: Tail = serde_json::from_slice(line).map_err(|e| anyhow!("broken journal last line: {}", e))?;
+    Ok(tail.seq)
+}
+
+/// 新しい keep 件だけを残して書き直す
+fn rotate(cfg: &Config, keep: usize) -> Result<()> {
+    let records = load(cfg)?;
+    if records.len() <= keep {
+        return Ok(());
+    }
+    let mut text = String::new();
+    for r in &records[records.len() - keep..] {
+        text.push_str(&serde_json::to_string(r)?);
+        text.push('\n');
+    }
+    store::write_atomic(&cfg.journal_path(), text.as_bytes())
+}
+
+pub fn load(cfg: &Config) -> Result<Vec<Record>> {
+    let text = match std::fs::read_to_string(cfg.journal_path()) {
+        Ok(s) => s,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .map(|(i, l)| serde_json::from_str(l).map_err(|e| anyhow!("broken journal line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// 取り消し済みの操作の seq
+fn undone_set(records: &[Record]) -> HashSet<u64> {
+    records.iter().filter_map(|r| r.undoes).collect()
+}
+
+/// 新しい順に最大 limit 件
+pub fn print_history(cfg: &Config, limit: usize) -> Result<()> {
+    let records = load(cfg)?;
+    if records.is_empty() {
+        println!("no history");
+        return Ok(());
+    }
+    let undone = undone_set(&records);
+    for r in records.iter().rev().take(limit) {
+        let at = DateTime::parse_from_rfc3339(&r.at)
+            .map(|d| d.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| r.at.clone());
+        let mark = if undone.contains(&r.seq) { "  (undone)" } else { "" };
+        println!("#{:<4} {}  {}  ({} file(s)){}", r.seq, at, r.summary, r.changes.len(), mark);
+    }
+    Ok(())
+}
+
+/// まだ取り消していない直近の操作を1件取り消す（取り消せるものが無ければ None）
+/// - 各ファイルが記録時の after のままであることを確認（違えば --force が無い限り中止）
+/// - after を消して before を書き戻す
+/// - 取り消し自体も "undo" として記録する Tx を返す（history に残る。undo の undo はしない）
+pub fn undo_last(cfg: &Config, force: bool) -> Result<Option<Tx>> {
+    let records = load(cfg)?;
+    let undone = undone_set(&records);
+    let Some(rec) = records.iter().rev().find(|r| r.undoes.is_none() && !undone.contains(&r.seq)) else {
+        return Ok(None);
+    };
+
+    let conflicts = conflicts(rec);
+    if !conflicts.is_empty() && !force {
+        let mut msg = format!("cannot undo #{} ({}): changed since\n", rec.seq, rec.summary);
+        for c in &conflicts {
+            msg.push_str(&format!("  {}\n", c));
+        }
+        msg.push_str("(use --force to overwrite)");
+        return Err(anyhow!(msg));
+    }
+
+    let mut tx = begin("undo", format!("undo #{}: {}", rec.seq, rec.summary));
+    tx.undoes = Some(rec.seq);
+    for c in rec.changes.iter().rev() {
+        revert(&mut tx, c)?;
+    }
+    println!("undone: #{} {}", rec.seq, rec.summary);
+    Ok(Some(tx))
+}
+
+fn conflicts(rec: &Record) -> Vec<String> {
+    let mut out = Vec::new();
+    for c in &rec.changes {
+        if let Some(p) = &c.after_path {
+            let now = std::fs::read_to_string(p).ok();
+            if now != c.after {
+                out.push(format!("{} was modified or removed", p.display()));
+            }
+        }
+        if let Some(p) = &c.before_path {
+            if Some(p) != c.after_path.as_ref() && p.exists() {
+                out.push(format!("{} already exists", p.display()));
+            }
+        }
+    }
+    out
+}
+
+fn revert(tx: &mut Tx, c: &FileChange) -> Result<()> {
+    match (&c.before_path, &c.before) {
+        (Some(bp), Some(before)) => {
+            if let Some(ap) = c.after_path.as_ref().filter(|ap| *ap != bp && ap.exists()) {
+                tx.note_rename(ap, bp);
+                if let Some(dir) = bp.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                std::fs::rename(ap, bp)?;
+            }
+            tx.note_write(bp);
+            store::write_atomic(bp, before.as_bytes())?;
+        }
+        _ => {
+            if let Some(ap) = c.after_path.as_ref().filter(|ap| ap.exists()) {
+                tx.note_write(ap);
+                std::fs::remove_file(ap)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(name: &str) -> Config {
+        let root = std::env::temp_dir().join(format!("todo-journal-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        Config { root_dir: root, ..Config::default() }
+    }
+
+    fn write(tx: &mut Tx, path: &Path, text: &str) {
+        tx.note_write(path);
+        std::fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn last_seq_reads_the_tail_even_with_long_lines() {
+        let cfg = config("tail");
+        assert_eq!(last_seq(&cfg.journal_path()).unwrap(), 0);
+
+        let big = "あ".repeat(40_000);
+        for i in 0..3 {
+            let mut tx = begin("edit", format!("edit {}", i));
+            write(&mut tx, &cfg.root_dir.join("a.md"), &format!("{}{}", big, i));
+            assert_eq!(tx.finish(&cfg).unwrap().unwrap().seq, i + 1);
+        }
+        assert_eq!(last_seq(&cfg.journal_path()).unwrap(), 3);
+        std::fs::remove_dir_all(&cfg.root_dir).unwrap();
+    }
+
+    #[test]
+    fn changes_belong_to_their_own_tx() {
+        let cfg = config("own");
+        let (a, b) = (cfg.root_dir.join("a.md"), cfg.root_dir.join("b.md"));
+        let mut tx = begin("edit", "edit a");
+        write(&mut tx, &a, "a");
+        // 別の Tx（や Tx なし）の書き込みは混ざらない
+        let other = begin("edit", "edit b");
+        std::fs::write(&b, "b").unwrap();
+        drop(other);
+
+        let rec = tx.finish(&cfg).unwrap().unwrap();
+        assert_eq!(rec.paths(), vec![a.clone()]);
+        assert_eq!(rec.changes[0].before, None);
+        assert_eq!(rec.changes[0].after.as_deref(), Some("a"));
+
+        // 変更なしなら何も書かない
+        let mut tx = begin("edit", "noop");
+        tx.note_write(&a);
+        assert!(tx.finish(&cfg).unwrap().is_none());
+        assert_eq!(load(&cfg).unwrap().len(), 1);
+        std::fs::remove_dir_all(&cfg.root_dir).unwrap();
+    }
+
+    #[test]
+    fn rotation_keeps_the_last_records() {
+        let mut cfg = config("rotate");
+        cfg.journal_keep_last = 3;
+        let path = cfg.root_dir.join("a.md");
+        for i in 0..ROTATE_EVERY + 1 {
+            let mut tx = begin("edit", format!("edit {}", i));
+            write(&mut tx, &path, &i.to_string());
+            tx.finish(&cfg).unwrap();
+        }
+        let seqs: Vec<u64> = load(&cfg).unwrap().iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, vec![ROTATE_EVERY - 2, ROTATE_EVERY - 1, ROTATE_EVERY, ROTATE_EVERY + 1]);
+        std::fs::remove_dir_all(&cfg.root_dir).unwrap();
+    }
+}
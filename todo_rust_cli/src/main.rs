@@ -4,33 +4,67 @@ mod model;
 mod frontmatter;
 mod store;
 mod format;
+mod recur;
+mod output;
+mod index;
+mod subtask;
+mod deps;
+mod filter;
+mod tui;
+mod picker;
+mod due;
+mod validate;
+mod doctor;
+mod journal;
+mod git;
+mod query;
+mod sort;
+mod agenda;
+mod ics;
+mod export;
+mod report;
+mod stats;
+mod activity;
+mod clock;
+mod focus;
+mod statusline;
+mod import;
 
 use anyhow::{anyhow, Result};
 use clap::Parser;
 use chrono::Local;
-use std::collections::HashSet;
 use std::process::Command;
 
-use cli::{Args, Cmd};
+use cli::{Args, ClockCmd, Cmd, ListFilter, ReportCmd, SubCmd};
 use config::Config;
 use model::{FrontMatter, Status, TodoFile};
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let cfg = Config::load();
+    let cfg = Config::load()?;
     store::ensure_dirs(&cfg)?;
 
     match args.cmd {
-        Cmd::Add { title, due, tags, importance, edit, slug } => {
-            cmd_add(&cfg, title, due, tags, importance, edit, slug)
+        Cmd::Add { title, due, tags, importance, edit, slug, blocked_by, repeat } => {
+            cmd_add(&cfg, title, due, tags, importance, edit, slug, blocked_by, repeat)
         }
-        Cmd::List { due_within, due_from, due_to, tag, status, importance, text, include_overdue } => {
-            cmd_list(&cfg, due_within, due_from, due_to, tag, status, importance, text, include_overdue)
+        Cmd::List { filter, format, columns } => cmd_list(&cfg, filter, format, columns),
+        Cmd::Agenda { days, filter } => cmd_agenda(&cfg, days, filter),
+        Cmd::Cal { month, filter } => cmd_cal(&cfg, month, filter),
+        Cmd::Export { format, to, output, all, component, group_by, filter } => {
+            let format = format.or(to).ok_or_else(|| anyhow!("missing export format"))?;
+            cmd_export(&cfg, format, output, all, component, group_by, filter)
         }
+        Cmd::Stats { since, until, tag, by } => {
+            stats::run(&cfg, since.as_deref(), until.as_deref(), tag.as_deref(), by)
+        }
+        Cmd::Import { from, file, dry_run } => import::run(&cfg, from, &file, dry_run),
+        Cmd::View { name, list, format, columns } => cmd_view(&cfg, name, list, format, columns),
+        Cmd::Tui { filter } => tui::run(&cfg, filter),
         Cmd::Show { id_or_prefix } => cmd_show(&cfg, &id_or_prefix),
         Cmd::Edit { id_or_prefix } => cmd_edit(&cfg, &id_or_prefix),
 
-        Cmd::Start { id_or_prefix } => cmd_start(&cfg, id_or_prefix),
+        Cmd::Start { id_or_prefix, force } => cmd_start(&cfg, id_or_prefix, force),
         Cmd::Wait { id_or_prefix } => cmd_wait(&cfg, id_or_prefix),
         Cmd::Done { id_or_prefix } => cmd_done(&cfg, id_or_prefix),
         Cmd::Cancel { id_or_prefix } => cmd_cancel(&cfg, id_or_prefix),
@@ -40,9 +74,36 @@ fn main() -> Result<()> {
         Cmd::Archive => cmd_archive(&cfg),
 
         Cmd::FixBroken => cmd_fix_broken(&cfg),
+
+        Cmd::Deps { id_or_prefix } => cmd_deps(&cfg, &id_or_prefix),
+        Cmd::Clock { cmd } => match cmd {
+            ClockCmd::In { id_or_prefix } => cmd_clock_in(&cfg, id_or_prefix),
+            ClockCmd::Out => clock::clock_out(&cfg),
+            ClockCmd::Status => clock::print_status(&cfg),
+        },
+        Cmd::Focus { id_or_prefix, minutes, bell, notify } => cmd_focus(&cfg, id_or_prefix, minutes, bell, notify),
+        Cmd::StatusLine { format, user_var, title_width } => {
+            statusline::run(&cfg, format.as_deref(), user_var.as_deref(), title_width)
+        }
+        Cmd::Report { cmd: ReportCmd::Time { week, since, until, tag } } => {
+            clock::report(&cfg, week, since.as_deref(), until.as_deref(), tag.as_deref())
+        }
+        Cmd::Log { id_or_prefix, since, tag, action } => cmd_log(&cfg, id_or_prefix, since, tag, action),
+
+        Cmd::Sub { cmd } => cmd_sub(&cfg, cmd),
+
+        Cmd::Reindex => cmd_reindex(&cfg),
+
+        Cmd::Doctor { fix } => doctor::run(&cfg, fix),
+
+        Cmd::Undo { n, force } => cmd_undo(&cfg, n, force),
+        Cmd::History { limit } => journal::print_history(&cfg, limit),
+
+        Cmd::Sync => cmd_sync(&cfg),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cmd_add(
     cfg: &Config,
     title: Option<String>,
@@ -51,37 +112,28 @@ fn cmd_add(
     importance: i32,
     edit: bool,
     slug_opt: Option<String>,
+    blocked_by: Vec<String>,
+    repeat: Option<String>,
 ) -> Result<()> {
-    let now = Local::now().to_rfc3339();
-    let id = now.clone();
+    if let Some(r) = repeat.as_deref() {
+        r.parse::<recur::Repeat>()?;
+    }
 
-    let file_ts = Local::now().format("%Y%m%d%H%M%S").to_string();
-    let slug = slug_opt
-        .or_else(|| title.as_ref().map(|t| slug::slugify(t)))
-        .filter(|s| !s.is_empty());
+    let due = due.as_deref().map(due::normalize).transpose()?;
 
-    let filename = if let Some(slug) = slug {
-        format!("{}__{}.md", file_ts, slug)
-    } else {
-        format!("{}.md", file_ts)
-    };
+    let blocked_by = blocked_by
+        .iter()
+        .map(|r| store::resolve_one_any(cfg, r).map(|t| t.fm.id))
+        .collect::<Result<Vec<_>>>()?;
 
-    let path = cfg.active_dir().join(filename);
+    let now = Local::now().to_rfc3339();
+    let id = now.clone();
 
-    let mut body = String::from("\n## メモ\n\n## サブタスク\n- [ ] \n\n## ログ\n- :\n");
+    let slug = slug_opt.or_else(|| title.as_deref().map(slug::slugify));
+    let lock = store::lock_root(cfg)?;
+    let path = store::new_active_path(cfg, slug.as_deref());
 
-    if cfg.template_path().exists() {
-        let tpl = std::fs::read_to_string(cfg.template_path())?;
-        let date = Local::now().format("%Y-%m-%d").to_string();
-        let t = tpl
-            .replace("{{id}}", &id)
-            .replace("{{title}}", title.as_deref().unwrap_or(""))
-            .replace("{{now}}", &now)
-            .replace("{{date}}", &date);
-
-        let parsed = frontmatter::parse_todo_file(path.clone(), &t)?;
-        body = parsed.body;
-    }
+    let body = new_body(cfg, &id, title.as_deref().unwrap_or(""), &now)?;
 
     let fm = FrontMatter {
         id,
@@ -94,148 +146,177 @@ fn cmd_add(
         updated_at: now,
         done_at: None,
         restored_from: None,
+        repeat,
+        blocked_by,
+        clock: Vec::new(),
+        pomodoros: 0,
     };
 
     let todo = TodoFile { path: path.clone(), fm, body };
-    store::save(&todo)?;
+    let mut tx = journal::begin("add", format!("add: {}", todo.fm.title));
+    let res = store::save(&mut tx, &todo);
+    finish_tx(cfg, tx)?;
+    res?;
+    drop(lock);
 
     if edit {
-        open_in_editor(cfg, &path)?;
-        cmd_touch_updated_at(&path)?;
+        edit_in_editor(cfg, &path, &todo.fm.title)?;
     } else {
         println!("created: {}", path.display());
     }
     Ok(())
 }
 
-fn cmd_list(
-    cfg: &Config,
-    due_within: Option<String>,
-    due_from: Option<String>,
-    due_to: Option<String>,
-    tag: Option<String>,
-    status: Option<String>,
-    importance_expr: Option<String>,
-    text: Option<String>,
-    include_overdue: bool,
-) -> Result<()> {
-    let mut todos = store::load_active(cfg)?;
-    todos.retain(|t| t.fm.status.is_active());
-
-    if let Some(s) = status {
-        let want: Status = s.parse()?;
-        todos.retain(|t| t.fm.status == want);
-    }
-
-    if let Some(tag) = tag {
-        let tag = tag.to_lowercase();
-        todos.retain(|t| t.fm.tags.iter().any(|x| x.to_lowercase() == tag));
+/// 新規 todo の本文（templates/todo.md があればその本文、無ければ既定の見出し）
+fn new_body(cfg: &Config, id: &str, title: &str, now: &str) -> Result<String> {
+    if !cfg.template_path().exists() {
+        return Ok(String::from("\n## メモ\n\n## サブタスク\n- [ ] \n\n## ログ\n- :\n"));
     }
+    let tpl = std::fs::read_to_string(cfg.template_path())?;
+    let date = Local::now().format("%Y-%m-%d").to_string();
+    let t = tpl
+        .replace("{{id}}", id)
+        .replace("{{title}}", title)
+        .replace("{{now}}", now)
+        .replace("{{date}}", &date);
 
-    if let Some(expr) = importance_expr {
-        let (op, n) = parse_importance_expr(&expr)?;
-        todos.retain(|t| compare_i32(t.fm.importance, op, n));
-    }
+    Ok(frontmatter::parse_todo_file(cfg.template_path(), &t)?.body)
+}
 
-    if let Some(q) = text {
-        if let Some(paths) = rg_paths(&cfg.active_dir(), &q) {
-            todos.retain(|t| paths.contains(&t.path));
-        } else {
-            let q = q.to_lowercase();
-            todos.retain(|t| t.fm.title.to_lowercase().contains(&q) || t.body.to_lowercase().contains(&q));
+fn cmd_list(cfg: &Config, filter: ListFilter, format: output::OutputFormat, columns: Option<String>) -> Result<()> {
+    let columns = match columns.as_deref() {
+        None => output::DEFAULT_COLUMNS.to_vec(),
+        Some(_) if format != output::OutputFormat::Table => {
+            return Err(anyhow!("--columns only applies to --format table"));
         }
-    }
-
-    let now_fixed = Local::now().with_timezone(Local::now().offset());
-
-    if let Some(within) = due_within {
-        let days = parse_days(&within)?;
-        let end = now_fixed + chrono::Duration::days(days);
-        todos.retain(|t| {
-            let Some(due_str) = t.fm.due.as_deref() else { return false; };
-            let Some(due_dt) = store::parse_due_dt(due_str) else { return false; };
-            if !include_overdue && due_dt < now_fixed { return false; }
-            due_dt >= now_fixed && due_dt <= end
-        });
-    } else if due_from.is_some() || due_to.is_some() {
-        let from_dt = due_from.as_deref().and_then(store::parse_due_dt);
-        let to_dt = due_to.as_deref().and_then(store::parse_due_dt);
-        todos.retain(|t| {
-            let Some(due_str) = t.fm.due.as_deref() else { return false; };
-            let Some(due_dt) = store::parse_due_dt(due_str) else { return false; };
-            if let Some(f) = from_dt { if due_dt < f { return false; } }
-            if let Some(to) = to_dt { if due_dt > to { return false; } }
-            true
-        });
-    }
-
-    todos.sort_by(|a, b| {
-        use std::cmp::Ordering;
-
-        let a_due = a.fm.due.as_deref().and_then(store::parse_due_dt);
-        let b_due = b.fm.due.as_deref().and_then(store::parse_due_dt);
-
-        let a_over = a_due.map(|d| d < now_fixed).unwrap_or(false);
-        let b_over = b_due.map(|d| d < now_fixed).unwrap_or(false);
+        Some(s) => output::parse_columns(s)?,
+    };
 
-        match (a_over, b_over) {
-            (true, false) => return Ordering::Less,
-            (false, true) => return Ordering::Greater,
-            _ => {}
-        }
+    let active = store::load_active(cfg)?;
+    let mut todos = active.clone();
+    todos.retain(|t| t.fm.status.is_active());
 
-        match (a_due, b_due) {
-            (Some(ad), Some(bd)) => if ad != bd { return ad.cmp(&bd); },
-            (Some(_), None) => return Ordering::Less,
-            (None, Some(_)) => return Ordering::Greater,
-            (None, None) => {}
-        }
+    let todos = filter::apply(cfg, todos, &filter)?;
+    output::print_todos(cfg, &todos, &active, format, &columns)
+}
 
-        if a.fm.importance != b.fm.importance {
-            return b.fm.importance.cmp(&a.fm.importance);
-        }
+fn cmd_agenda(cfg: &Config, days: i64, filter: ListFilter) -> Result<()> {
+    let active = store::load_active(cfg)?;
+    let mut todos = active.clone();
+    todos.retain(|t| t.fm.status.is_active());
 
-        a.fm.id.cmp(&b.fm.id)
-    });
+    let todos = filter::apply(cfg, todos, &filter)?;
+    agenda::print_agenda(cfg, &todos, &active, days)
+}
 
-    let enable_color = std::env::var("NO_COLOR").is_err();
+fn cmd_cal(cfg: &Config, month: Option<String>, filter: ListFilter) -> Result<()> {
+    let active = store::load_active(cfg)?;
+    let mut todos = active.clone();
+    todos.retain(|t| t.fm.status.is_active());
 
-    for t in &todos {
-        let l = format::label_for(t, cfg.soon_days);
-        let lab = format::label_colored(l, enable_color);
-        let due = format::due_display(t);
-        let imp = format!("[{}]", t.fm.importance);
-        let title = format::truncate(&t.fm.title, 40);
-        let tags = if t.fm.tags.is_empty() { "".to_string() } else { format!(" ({})", t.fm.tags.join(",")) };
+    let todos = filter::apply(cfg, todos, &filter)?;
+    agenda::print_cal(cfg, &todos, &active, month.as_deref())
+}
 
-        println!(
-            "{:<7} {:<10} {:<6} {:<12} {:<40}{}",
-            lab, due, imp, t.short_id(), title, tags
-        );
+fn cmd_export(
+    cfg: &Config,
+    format: export::ExportFormat,
+    output: Option<std::path::PathBuf>,
+    all: bool,
+    component: Option<ics::Component>,
+    group_by: Option<report::GroupBy>,
+    filter: ListFilter,
+) -> Result<()> {
+    if group_by.is_some() && !format.is_report() {
+        return Err(anyhow!("--group-by only applies to markdown/html"));
     }
+    let mut todos = if all { store::load_all(cfg)? } else { store::load_active(cfg)? };
+    if !all {
+        todos.retain(|t| t.fm.status.is_active());
+    }
+    let todos = filter::apply(cfg, todos, &filter)?;
+    let mut heading = filter::describe(&filter);
+    if all {
+        heading = format!("all {}", heading).trim_end().to_string();
+    }
+    export::run(
+        format,
+        &todos,
+        output.as_deref(),
+        component.unwrap_or(cfg.ics.component),
+        group_by.unwrap_or_default(),
+        &heading,
+    )
+}
 
-    Ok(())
+fn cmd_view(
+    cfg: &Config,
+    name: Option<String>,
+    list: bool,
+    format: Option<output::OutputFormat>,
+    columns: Option<String>,
+) -> Result<()> {
+    let name = match name {
+        Some(n) if !list => n,
+        _ => {
+            if cfg.views.is_empty() {
+                println!("no views (define [views.<name>] in config.toml)");
+            }
+            for (name, view) in &cfg.views {
+                let desc = view.description.as_deref().map(|d| format!("{}  ", d)).unwrap_or_default();
+                println!("{:<12} {}[{}]", name, desc, filter::describe(&view.filter));
+            }
+            return Ok(());
+        }
+    };
+    let view = cfg.view(&name)?;
+    let format = format.or(view.format).unwrap_or(output::OutputFormat::Table);
+    cmd_list(cfg, view.filter.clone(), format, columns.or_else(|| view.columns.clone()))
 }
 
 fn cmd_show(cfg: &Config, id_or_prefix: &str) -> Result<()> {
     let todo = store::resolve_one(cfg, id_or_prefix)?;
     let text = std::fs::read_to_string(&todo.path)?;
     println!("{}", text);
+
+    let (done, total) = subtask::progress(&todo.body);
+    if total > 0 {
+        println!("subtasks: {}/{}", done, total);
+    }
     Ok(())
 }
 
 fn cmd_edit(cfg: &Config, id_or_prefix: &str) -> Result<()> {
     let todo = store::resolve_one(cfg, id_or_prefix)?;
-    let path = todo.path.clone();
+    edit_in_editor(cfg, &todo.path, &todo.fm.title)?;
+    println!("updated: {}", todo.path.display());
+    Ok(())
+}
 
-    open_in_editor(cfg, &path)?;
-    cmd_touch_updated_at(&path)?;
+/// エディタで開き、閉じたら updated_at を更新する
+/// エディタでの変更も含めて1操作として journal に残す（undo・auto-commit・ics の対象になる）
+fn edit_in_editor(cfg: &Config, path: &std::path::Path, title: &str) -> Result<()> {
+    // エディタで変わる前の内容から記録する
+    let mut tx = journal::begin("edit", format!("edit: {}", title));
+    tx.note_write(path);
+    open_in_editor(cfg, path)?;
+    let _lock = store::lock_root(cfg)?;
+    let res = touch_updated_at(&mut tx, path);
+    finish_tx(cfg, tx)?;
+    res
+}
 
-    println!("updated: {}", path.display());
+/// journal に記録し、変更があればその分を auto-commit して ics を書き出し直す
+/// 更新系コマンドは journal::begin したら必ずここを通す
+fn finish_tx(cfg: &Config, tx: journal::Tx) -> Result<()> {
+    let Some(rec) = tx.finish(cfg)? else { return Ok(()); };
+    let paths = rec.paths();
+    git::auto_commit(cfg, &rec.summary, &paths);
+    ics::refresh_feed(cfg, &paths);
     Ok(())
 }
 
-fn cmd_touch_updated_at(path: &std::path::Path) -> Result<()> {
+fn touch_updated_at(tx: &mut journal::Tx, path: &std::path::Path) -> Result<()> {
     let text = std::fs::read_to_string(path)?;
     let mut edited = frontmatter::parse_todo_file(path.to_path_buf(), &text)?;
     edited.fm.updated_at = store::now_jst_rfc3339();
@@ -244,7 +325,7 @@ fn cmd_touch_updated_at(path: &std::path::Path) -> Result<()> {
         edited.fm.done_at = Some(store::now_jst_rfc3339());
     }
 
-    store::save(&edited)?;
+    store::save(tx, &edited)?;
     Ok(())
 }
 
@@ -262,35 +343,80 @@ fn pick_or_prefix(
     Ok(None)
 }
 
-fn cmd_start(cfg: &Config, id_or_prefix: Option<String>) -> Result<()> {
-    if let Some(id) = pick_or_prefix(cfg, id_or_prefix, store::select_active_one_fzf)? {
-        return cmd_set_status(cfg, &id, Status::Doing);
+fn cmd_start(cfg: &Config, id_or_prefix: Option<String>, force: bool) -> Result<()> {
+    if let Some(id) = pick_or_prefix(cfg, id_or_prefix, store::select_active_one)? {
+        let todo = store::resolve_one(cfg, &id)?;
+        if let Some(mut msg) = blocked_message(cfg, &todo)? {
+            if !force {
+                msg.push_str("(use --force to start anyway)");
+                return Err(anyhow!(msg));
+            }
+            eprint!("warning: {}", msg);
+        }
+        return cmd_set_status(cfg, &todo.fm.id, Status::Doing);
+    }
+    println!("no selection (canceled / no candidates)");
+    Ok(())
+}
+
+/// blocked でも計測は始める（警告だけ）
+fn cmd_clock_in(cfg: &Config, id_or_prefix: Option<String>) -> Result<()> {
+    if let Some(id) = pick_or_prefix(cfg, id_or_prefix, store::select_active_one)? {
+        let todo = store::resolve_one(cfg, &id)?;
+        if todo.fm.status != Status::Doing {
+            if let Some(msg) = blocked_message(cfg, &todo)? {
+                eprint!("warning: {}", msg);
+            }
+        }
+        return clock::clock_in(cfg, &todo.fm.id);
+    }
+    println!("no selection (canceled / no candidates)");
+    Ok(())
+}
+
+fn cmd_focus(cfg: &Config, id_or_prefix: Option<String>, minutes: Option<u32>, bell: bool, notify: bool) -> Result<()> {
+    if let Some(id) = pick_or_prefix(cfg, id_or_prefix, store::select_active_one)? {
+        return focus::run(cfg, &id, minutes, bell, notify);
     }
-    println!("no selection (fzf not available / canceled / no candidates)");
+    println!("no selection (canceled / no candidates)");
     Ok(())
 }
 
+/// 未完了の blocker があれば一覧メッセージを返す
+fn blocked_message(cfg: &Config, todo: &TodoFile) -> Result<Option<String>> {
+    let active = store::load_active(cfg)?;
+    let blockers = deps::active_blockers(todo, &active);
+    if blockers.is_empty() {
+        return Ok(None);
+    }
+    let mut msg = format!("blocked by {} active todo(s):\n", blockers.len());
+    for b in &blockers {
+        msg.push_str(&format!("  {}  [{}]  {}\n", b.short_id(), b.fm.status.as_str(), b.fm.title));
+    }
+    Ok(Some(msg))
+}
+
 fn cmd_wait(cfg: &Config, id_or_prefix: Option<String>) -> Result<()> {
-    if let Some(id) = pick_or_prefix(cfg, id_or_prefix, store::select_active_one_fzf)? {
+    if let Some(id) = pick_or_prefix(cfg, id_or_prefix, store::select_active_one)? {
         return cmd_set_status(cfg, &id, Status::Waiting);
     }
-    println!("no selection (fzf not available / canceled / no candidates)");
+    println!("no selection (canceled / no candidates)");
     Ok(())
 }
 
 fn cmd_done(cfg: &Config, id_or_prefix: Option<String>) -> Result<()> {
-    if let Some(id) = pick_or_prefix(cfg, id_or_prefix, store::select_active_one_fzf)? {
+    if let Some(id) = pick_or_prefix(cfg, id_or_prefix, store::select_active_one)? {
         return cmd_set_status(cfg, &id, Status::Done);
     }
-    println!("no selection (fzf not available / canceled / no candidates)");
+    println!("no selection (canceled / no candidates)");
     Ok(())
 }
 
 fn cmd_cancel(cfg: &Config, id_or_prefix: Option<String>) -> Result<()> {
-    if let Some(id) = pick_or_prefix(cfg, id_or_prefix, store::select_active_one_fzf)? {
+    if let Some(id) = pick_or_prefix(cfg, id_or_prefix, store::select_active_one)? {
         return cmd_set_status(cfg, &id, Status::Canceled);
     }
-    println!("no selection (fzf not available / canceled / no candidates)");
+    println!("no selection (canceled / no candidates)");
     Ok(())
 }
 
@@ -299,18 +425,34 @@ fn cmd_cancel(cfg: &Config, id_or_prefix: Option<String>) -> Result<()> {
 /// - active/ に戻してリネーム（TS+slug）
 /// - restored_from を記録
 fn cmd_reopen(cfg: &Config, id_or_prefix: Option<String>) -> Result<()> {
-    let mut todo = if let Some(s) = id_or_prefix {
+    let todo = if let Some(s) = id_or_prefix {
         store::resolve_one_closed(cfg, &s)?
     } else {
-        match store::select_closed_one_fzf(cfg)? {
+        match store::select_closed_one(cfg)? {
             Some(t) => t,
             None => {
-                println!("no selection (fzf not available / canceled / no closed todos)");
+                println!("no selection (canceled / no closed todos)");
                 return Ok(());
             }
         }
     };
 
+    let _lock = store::lock_root(cfg)?;
+    let todo = store::reload(&todo)?;
+    let path = reopen_todo(cfg, todo)?;
+    println!("reopened: {}", path.display());
+    Ok(())
+}
+
+/// reopen の本体：active/ へ移動・リネームし、status=todo で保存して新しいパスを返す
+fn reopen_todo(cfg: &Config, todo: TodoFile) -> Result<std::path::PathBuf> {
+    let mut tx = journal::begin("reopen", format!("reopen: {}", todo.fm.title));
+    let res = reopen_in_place(cfg, &mut tx, todo);
+    finish_tx(cfg, tx)?;
+    res
+}
+
+fn reopen_in_place(cfg: &Config, tx: &mut journal::Tx, mut todo: TodoFile) -> Result<std::path::PathBuf> {
     if !matches!(todo.fm.status, Status::Done | Status::Canceled) {
         anyhow::bail!(
             "reopen is only allowed for status done/canceled, but got: {}",
@@ -320,11 +462,11 @@ fn cmd_reopen(cfg: &Config, id_or_prefix: Option<String>) -> Result<()> {
 
     let src_str = todo.path.display().to_string();
 
-    let new_path = store::move_to_active(cfg, &todo)?;
+    let new_path = store::move_to_active(cfg, tx, &todo)?;
     todo.path = new_path;
 
     let now = store::now_jst_rfc3339();
-    let date = Local::now().format("%Y-%m-%d").to_string();
+    let at = activity::stamp();
 
     let prev = todo.fm.status.clone();
     let next = Status::Todo;
@@ -335,17 +477,42 @@ fn cmd_reopen(cfg: &Config, id_or_prefix: Option<String>) -> Result<()> {
     todo.fm.restored_from = Some(src_str);
 
     let msg = format!("reopen (status {} -> {})", prev.as_str(), next.as_str());
-    todo.append_log_line(&date, &msg);
+    todo.append_log_line(&at, &msg);
 
-    store::save(&todo)?;
-    println!("reopened: {}", todo.path.display());
-    Ok(())
+    store::save(tx, &todo)?;
+    Ok(todo.path)
 }
 
 fn cmd_set_status(cfg: &Config, id_or_prefix: &str, status: Status) -> Result<()> {
-    let mut todo = store::resolve_one(cfg, id_or_prefix)?;
+    // 候補が複数あるとピッカーが開くので、lock は選んでから取って読み直す
+    let todo = store::resolve_one(cfg, id_or_prefix)?;
+    let _lock = store::lock_root(cfg)?;
+    let todo = store::reload(&todo)?;
+    for line in apply_status(cfg, todo, status)? {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// status 変更の本体（表示はせず、"updated: ..." 等の結果行を返す）
+fn apply_status(cfg: &Config, todo: TodoFile, status: Status) -> Result<Vec<String>> {
+    let verb = match status {
+        Status::Todo => "todo",
+        Status::Doing => "start",
+        Status::Waiting => "wait",
+        Status::Done => "done",
+        Status::Canceled => "cancel",
+    };
+    let mut tx = journal::begin(verb, format!("{}: {}", verb, todo.fm.title));
+    let res = set_status(cfg, &mut tx, todo, status);
+    finish_tx(cfg, tx)?;
+    res
+}
+
+fn set_status(cfg: &Config, tx: &mut journal::Tx, mut todo: TodoFile, status: Status) -> Result<Vec<String>> {
+    let mut out = Vec::new();
     let now = store::now_jst_rfc3339();
-    let date = Local::now().format("%Y-%m-%d").to_string();
+    let at = activity::stamp();
 
     let prev = todo.fm.status.clone();
 
@@ -366,35 +533,103 @@ fn cmd_set_status(cfg: &Config, id_or_prefix: &str, status: Status) -> Result<()
         (_, Status::Todo) => "reopen",
     };
 
+    // doing から外れたら計測も止める
+    if status != Status::Doing {
+        if let Some(secs) = clock::stop(&mut todo) {
+            out.push(format!("clocked out: {}", clock::fmt_duration(secs)));
+        }
+    }
+
     let msg = format!("{} (status {} -> {})", action, prev.as_str(), status.as_str());
-    todo.append_log_line(&date, &msg);
+    todo.append_log_line(&at, &msg);
+
+    let spawned = if status == Status::Done && prev != Status::Done {
+        next_occurrence(cfg, &mut todo)?
+    } else {
+        None
+    };
 
-    store::save(&todo)?;
+    // done にした側を先に書く（次回分だけが残って二重に active になるのを避ける）
+    store::save(tx, &todo)?;
+
+    if let Some(next) = spawned {
+        store::save(tx, &next)?;
+        out.push(format!("spawned: {}", next.path.display()));
+    }
 
     if cfg.auto_archive && matches!(todo.fm.status, Status::Done | Status::Canceled) {
-        let dest = store::move_to_archive(cfg, &todo)?;
-        println!("archived: {}", dest.display());
+        let dest = store::move_to_archive(cfg, tx, &todo)?;
+        out.push(format!("archived: {}", dest.display()));
     } else {
-        println!("updated: {}", todo.path.display());
+        out.push(format!("updated: {}", todo.path.display()));
     }
 
-    Ok(())
+    Ok(out)
+}
+
+/// repeat 付きの todo が done になったときの次回分（active/ に置く。書き込みは呼び出し側）
+/// - tags/importance/repeat/本文テンプレを引き継ぐ（チェックは外し、ログは空に）
+/// - 双方のログに相手の id を残す
+/// - 次回分が不正なら prev にも手を付けずにエラー
+fn next_occurrence(cfg: &Config, prev: &mut TodoFile) -> Result<Option<TodoFile>> {
+    let Some(spec) = prev.fm.repeat.as_deref() else { return Ok(None); };
+    let repeat: recur::Repeat = spec.parse()?;
+    let due = recur::next_due(&repeat, prev.fm.due.as_deref())
+        .ok_or_else(|| anyhow!("failed to compute next due for repeat: {}", spec))?;
+
+    let now = store::now_jst_rfc3339();
+    let at = activity::stamp();
+
+    let slug = slug::slugify(&prev.fm.title);
+    let path = store::new_active_path(cfg, Some(&slug));
+
+    let fm = FrontMatter {
+        id: now.clone(),
+        title: prev.fm.title.clone(),
+        status: Status::Todo,
+        due: Some(due.clone()),
+        tags: prev.fm.tags.clone(),
+        importance: prev.fm.importance,
+        created_at: now.clone(),
+        updated_at: now,
+        done_at: None,
+        restored_from: None,
+        repeat: Some(recur::next_spec(spec, &repeat, prev.fm.due.as_deref())),
+        blocked_by: Vec::new(),
+        clock: Vec::new(),
+        pomodoros: 0,
+    };
+
+    let mut next = TodoFile { path: path.clone(), fm, body: recur::fresh_body(&prev.body) };
+    next.append_log_line(&at, &format!("repeat: spawned from {} ({})", prev.fm.id, prev.path.display()));
+    validate::ensure_valid(&next, None)?;
+
+    prev.append_log_line(&at, &format!("repeat: next {} (due {})", next.fm.id, due));
+    Ok(Some(next))
 }
 
 fn cmd_archive(cfg: &Config) -> Result<()> {
+    let _lock = store::lock_root(cfg)?;
+    let mut tx = journal::begin("archive", "archive");
+    let res = archive_all(cfg, &mut tx);
+    finish_tx(cfg, tx)?;
+    res
+}
+
+fn archive_all(cfg: &Config, tx: &mut journal::Tx) -> Result<()> {
     // 1) active/ の done/canceled を archive へ
     let todos = store::load_active(cfg)?;
     let mut moved_from_active = 0;
 
     for t in todos {
         if matches!(t.fm.status, Status::Done | Status::Canceled) && t.fm.done_at.is_some() {
-            store::move_to_archive(cfg, &t)?;
+            store::move_to_archive(cfg, tx, &t)?;
             moved_from_active += 1;
         }
     }
 
     // 2) archive 内を整理（statusズレ修正、active復旧、broken隔離、YYYY/MM整形）
-    let reorganized = store::organize_archive(cfg)?;
+    let reorganized = store::organize_archive(cfg, tx)?;
 
     println!(
         "archived {} file(s) from active, reorganized {} file(s) in archive",
@@ -405,93 +640,160 @@ fn cmd_archive(cfg: &Config) -> Result<()> {
 
 /// done/broken と canceled/broken から選んで修復
 fn cmd_fix_broken(cfg: &Config) -> Result<()> {
-    let Some(path) = store::select_broken_path_fzf(cfg)? else {
-        println!("no broken files (or fzf not available / canceled)");
+    let Some(path) = store::select_broken_path(cfg)? else {
+        println!("no broken files (or canceled)");
         return Ok(());
     };
 
+    // エディタで直す前の内容から記録する
+    let mut tx = journal::begin("fix-broken", format!("fix-broken: {}", path.display()));
+    tx.note_write(&path);
     open_in_editor(cfg, &path)?;
 
+    let _lock = store::lock_root(cfg)?;
     let text = std::fs::read_to_string(&path)?;
     let todo = match frontmatter::parse_todo_file(path.clone(), &text) {
         Ok(t) => t,
         Err(e) => {
+            // 直しかけの編集も記録しておく
+            finish_tx(cfg, tx)?;
             println!("still broken: {} ({})", path.display(), e);
             return Ok(());
         }
     };
 
-    let dest = store::place_todo_by_status(cfg, &todo)?;
+    let res = store::place_todo_by_status(cfg, &mut tx, &todo);
+    finish_tx(cfg, tx)?;
+    let dest = res?;
     println!("fixed and placed: {}", dest.display());
     Ok(())
 }
 
-fn open_in_editor(cfg: &Config, path: &std::path::Path) -> Result<()> {
-    let editor = &cfg.editor;
-    let status = Command::new(editor)
-        .arg(path)
-        .status()
-        .map_err(|e| anyhow!("failed to launch editor '{}': {}", editor, e))?;
-    if !status.success() {
-        return Err(anyhow!("editor exited with non-zero status"));
+fn cmd_log(
+    cfg: &Config,
+    id_or_prefix: Option<String>,
+    since: Option<String>,
+    tag: Option<String>,
+    action: Option<String>,
+) -> Result<()> {
+    let since = since.as_deref().map(due::parse_past).transpose()?;
+    if let Some(id) = id_or_prefix.as_deref() {
+        let todo = store::resolve_one_any(cfg, id)?;
+        activity::print_todo(&todo, since, action.as_deref());
+        return Ok(());
+    }
+
+    let mut todos = store::load_all(cfg)?;
+    if let Some(tag) = tag {
+        let tag = tag.to_lowercase();
+        todos.retain(|t| t.fm.tags.iter().any(|x| x.to_lowercase() == tag));
     }
+    let since = match since {
+        Some(d) => d,
+        None => due::parse_past("7d")?,
+    };
+    activity::print_timeline(&todos, since, action.as_deref());
     Ok(())
 }
 
-fn parse_days(s: &str) -> Result<i64> {
-    let s = s.trim().to_lowercase();
-    if let Some(num) = s.strip_suffix('d') {
-        return Ok(num.parse::<i64>()?);
-    }
-    Err(anyhow!("invalid duration: {} (use like 14d)", s))
-}
-
-#[derive(Debug, Clone, Copy)]
-enum Op { Eq, Gt, Ge, Lt, Le }
-
-fn parse_importance_expr(s: &str) -> Result<(Op, i32)> {
-    let s = s.trim();
-    for (p, op) in [
-        (">=", Op::Ge),
-        ("<=", Op::Le),
-        (">", Op::Gt),
-        ("<", Op::Lt),
-        ("=", Op::Eq),
-    ] {
-        if let Some(rest) = s.strip_prefix(p) {
-            return Ok((op, rest.trim().parse()?));
+fn cmd_deps(cfg: &Config, id_or_prefix: &str) -> Result<()> {
+    let all = store::load_all(cfg)?;
+    let todo = store::resolve_one_any(cfg, id_or_prefix)?;
+
+    print!("{}", deps::render_tree(&todo, &all));
+
+    let dependents = deps::dependents(&todo, &all);
+    if !dependents.is_empty() {
+        println!("\nblocks:");
+        for t in dependents {
+            println!("  {}  [{}]  {}", t.short_id(), t.fm.status.as_str(), t.fm.title);
         }
     }
-    Ok((Op::Eq, s.parse()?))
+    Ok(())
+}
+
+fn cmd_sub(cfg: &Config, cmd: SubCmd) -> Result<()> {
+    let (id_or_prefix, item) = match &cmd {
+        SubCmd::Add { id_or_prefix, text } => (id_or_prefix, text),
+        SubCmd::Check { id_or_prefix, item }
+        | SubCmd::Uncheck { id_or_prefix, item }
+        | SubCmd::Rm { id_or_prefix, item } => (id_or_prefix, item),
+    };
+
+    let todo = store::resolve_one(cfg, id_or_prefix)?;
+    let _lock = store::lock_root(cfg)?;
+    let mut todo = store::reload(&todo)?;
+    let items = subtask::parse(&todo.body);
+
+    let (body, msg) = match &cmd {
+        SubCmd::Add { .. } => {
+            if item.trim().is_empty() {
+                anyhow::bail!("subtask text is empty");
+            }
+            (subtask::add(&todo.body, item), format!("subtask add: {}", item.trim()))
+        }
+        SubCmd::Check { .. } => {
+            let t = subtask::find(&items, item)?;
+            (subtask::set_done(&todo.body, t, true), format!("subtask check: {}", t.text))
+        }
+        SubCmd::Uncheck { .. } => {
+            let t = subtask::find(&items, item)?;
+            (subtask::set_done(&todo.body, t, false), format!("subtask uncheck: {}", t.text))
+        }
+        SubCmd::Rm { .. } => {
+            let t = subtask::find(&items, item)?;
+            (subtask::remove(&todo.body, t), format!("subtask rm: {}", t.text))
+        }
+    };
+
+    let at = activity::stamp();
+    todo.body = body;
+    todo.fm.updated_at = store::now_jst_rfc3339();
+    todo.append_log_line(&at, &msg);
+    let mut tx = journal::begin("sub", format!("{}: {}", msg, todo.fm.title));
+    let res = store::save(&mut tx, &todo);
+    finish_tx(cfg, tx)?;
+    res?;
+
+    let (done, total) = subtask::progress(&todo.body);
+    println!("updated: {} (subtasks {}/{})", todo.path.display(), done, total);
+    Ok(())
 }
 
-fn compare_i32(v: i32, op: Op, n: i32) -> bool {
-    match op {
-        Op::Eq => v == n,
-        Op::Gt => v > n,
-        Op::Ge => v >= n,
-        Op::Lt => v < n,
-        Op::Le => v <= n,
+fn cmd_undo(cfg: &Config, n: usize, force: bool) -> Result<()> {
+    let _lock = store::lock_root(cfg)?;
+    for i in 0..n {
+        let Some(tx) = journal::undo_last(cfg, force)? else {
+            if i == 0 {
+                println!("nothing to undo");
+            }
+            break;
+        };
+        finish_tx(cfg, tx)?;
     }
+    Ok(())
 }
 
-fn rg_paths(dir: &std::path::Path, query: &str) -> Option<HashSet<std::path::PathBuf>> {
-    let out = Command::new("rg")
-        .arg("-l")
-        .arg(query)
-        .arg(dir)
-        .output()
-        .ok()?;
+fn cmd_sync(cfg: &Config) -> Result<()> {
+    let _lock = store::lock_root(cfg)?;
+    git::sync(cfg)
+}
 
-    if !out.status.success() { return None; }
+fn cmd_reindex(cfg: &Config) -> Result<()> {
+    let _lock = store::lock_root(cfg)?;
+    let n = store::rebuild_index(cfg)?;
+    println!("indexed {} file(s): {}, {}", n, cfg.index_path().display(), cfg.archive_index_path().display());
+    Ok(())
+}
 
-    let s = String::from_utf8_lossy(&out.stdout);
-    let mut set = HashSet::new();
-    for line in s.lines() {
-        let p = line.trim();
-        if !p.is_empty() {
-            set.insert(std::path::PathBuf::from(p));
-        }
+fn open_in_editor(cfg: &Config, path: &std::path::Path) -> Result<()> {
+    let editor = &cfg.editor;
+    let status = Command::new(editor)
+        .arg(path)
+        .status()
+        .map_err(|e| anyhow!("failed to launch editor '{}': {}", editor, e))?;
+    if !status.success() {
+        return Err(anyhow!("editor exited with non-zero status"));
     }
-    Some(set)
+    Ok(())
 }
@@ -66,6 +66,31 @@ pub struct FrontMatter {
     /// archive等から戻したときの元パス記録（任意）
     #[serde(default)]
     pub restored_from: Option<String>,
+
+    /// 繰り返し指定（例: "every 1w", "monthly on 25", "weekdays"）
+    #[serde(default)]
+    pub repeat: Option<String>,
+
+    /// 先に終わっている必要がある todo の id（prefix 可）
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
+
+    /// `todo clock in/out` で記録した作業区間
+    #[serde(default)]
+    pub clock: Vec<ClockEntry>,
+
+    /// `todo focus` で最後までやりきったポモドーロの数
+    #[serde(default)]
+    pub pomodoros: u32,
+}
+
+/// 作業区間1つ（RFC3339。end が無いものは計測中）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ClockEntry {
+    pub start: String,
+
+    #[serde(default)]
+    pub end: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -80,8 +105,9 @@ impl TodoFile {
         self.fm.id.chars().take(12).collect()
     }
 
-    pub fn append_log_line(&mut self, date: &str, message: &str) {
-        let line = format!("- {}: {}\n", date, message);
+    /// `## ログ` の先頭に `- <at>: <message>` を足す（at は activity::stamp() の RFC3339）
+    pub fn append_log_line(&mut self, at: &str, message: &str) {
+        let line = format!("- {}: {}\n", at, message);
 
         if !self.body.contains("\n## ログ") && !self.body.starts_with("## ログ") {
             if !self.body.ends_with('\n') {
@@ -0,0 +1,376 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::io::{IsTerminal, Write};
+
+use crate::config::Config;
+use crate::format;
+use crate::model::{FrontMatter, TodoFile};
+use crate::{clock, subtask};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Ndjson,
+    Csv,
+    Tsv,
+}
+
+/// table 形式の列（--columns で選んで並べ替える）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Label,
+    Due,
+    Importance,
+    Subtasks,
+    Clock,
+    Pomodoros,
+    Id,
+    Title,
+    Tags,
+    Status,
+    Created,
+    Updated,
+    Repeat,
+    Path,
+}
+
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Label,
+    Column::Due,
+    Column::Importance,
+    Column::Subtasks,
+    Column::Clock,
+    Column::Id,
+    Column::Title,
+    Column::Tags,
+];
+
+/// 端末幅が足りないときでも title はこれより縮めない
+const TITLE_MIN_WIDTH: usize = 10;
+
+impl Column {
+    /// 最小幅（既定の列は以前の固定幅のまま揃える。全行で空の列には使わない）
+    fn min_width(self) -> usize {
+        match self {
+            Column::Label => 7,
+            Column::Due => 10,
+            Column::Importance => 6,
+            Column::Subtasks => 5,
+            Column::Id => 12,
+            Column::Status => 7,
+            Column::Created | Column::Updated => 10,
+            Column::Clock | Column::Pomodoros | Column::Title | Column::Tags | Column::Repeat | Column::Path => 0,
+        }
+    }
+}
+
+/// "label,due,title" のようなカンマ区切り
+pub fn parse_columns(s: &str) -> Result<Vec<Column>> {
+    let mut out = Vec::new();
+    for name in s.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let c = match name.to_lowercase().as_str() {
+            "label" => Column::Label,
+            "due" => Column::Due,
+            "importance" | "imp" => Column::Importance,
+            "subtasks" | "sub" => Column::Subtasks,
+            "clock" | "time" => Column::Clock,
+            "pomodoros" | "pomo" => Column::Pomodoros,
+            "id" | "short_id" => Column::Id,
+            "title" => Column::Title,
+            "tags" | "tag" => Column::Tags,
+            "status" => Column::Status,
+            "created" | "created_at" => Column::Created,
+            "updated" | "updated_at" => Column::Updated,
+            "repeat" => Column::Repeat,
+            "path" => Column::Path,
+            _ => {
+                return Err(anyhow!(
+                    "unknown column: {} (label/due/importance/subtasks/clock/pomodoros/id/title/tags/status/created/updated/repeat/path)",
+                    name
+                ))
+            }
+        };
+        out.push(c);
+    }
+    if out.is_empty() {
+        return Err(anyhow!("empty --columns"));
+    }
+    Ok(out)
+}
+
+/// 機械可読出力の1レコード（json/ndjson）
+/// - frontmatter の全フィールド（キー名は YAML と同じ）
+/// - 計算値: label / path / short_id / subtasks_done / subtasks_total / clocked_minutes
+///
+/// フィールドは追加されることはあっても、改名・削除はしない。
+#[derive(Debug, Serialize)]
+pub struct TodoRecord<'a> {
+    #[serde(flatten)]
+    pub fm: &'a FrontMatter,
+    pub label: &'static str,
+    pub path: String,
+    pub short_id: String,
+    pub subtasks_done: usize,
+    pub subtasks_total: usize,
+    pub clocked_minutes: i64,
+}
+
+impl<'a> TodoRecord<'a> {
+    pub fn new(todo: &'a TodoFile, label: format::Label) -> Self {
+        let (subtasks_done, subtasks_total) = subtask::progress(&todo.body);
+        Self {
+            fm: &todo.fm,
+            label: format::label_key(label),
+            path: todo.path.display().to_string(),
+            short_id: todo.short_id(),
+            subtasks_done,
+            subtasks_total,
+            clocked_minutes: clock::total_seconds(todo) / 60,
+        }
+    }
+}
+
+/// csv/tsv の列（この順序で固定。新しい列は末尾に追加する）
+pub const DELIMITED_COLUMNS: &[&str] = &[
+    "id",
+    "short_id",
+    "title",
+    "status",
+    "label",
+    "due",
+    "importance",
+    "tags",
+    "created_at",
+    "updated_at",
+    "done_at",
+    "restored_from",
+    "repeat",
+    "path",
+    "subtasks_done",
+    "subtasks_total",
+    "blocked_by",
+    "clocked_minutes",
+    "pomodoros",
+];
+
+fn delimited_values(todo: &TodoFile, label: format::Label) -> Vec<String> {
+    let fm = &todo.fm;
+    let (sub_done, sub_total) = subtask::progress(&todo.body);
+    vec![
+        fm.id.clone(),
+        todo.short_id(),
+        fm.title.clone(),
+        fm.status.as_str().to_string(),
+        format::label_key(label).to_string(),
+        fm.due.clone().unwrap_or_default(),
+        fm.importance.to_string(),
+        fm.tags.join(","),
+        fm.created_at.clone(),
+        fm.updated_at.clone(),
+        fm.done_at.clone().unwrap_or_default(),
+        fm.restored_from.clone().unwrap_or_default(),
+        fm.repeat.clone().unwrap_or_default(),
+        todo.path.display().to_string(),
+        sub_done.to_string(),
+        sub_total.to_string(),
+        fm.blocked_by.join(","),
+        (clock::total_seconds(todo) / 60).to_string(),
+        fm.pomodoros.to_string(),
+    ]
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn tsv_field(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// active は BLOCKED 判定用の active/ 全件（フィルタ前）
+/// columns は table のときだけ使う
+pub fn print_todos(
+    cfg: &Config,
+    todos: &[TodoFile],
+    active: &[TodoFile],
+    fmt: OutputFormat,
+    columns: &[Column],
+) -> Result<()> {
+    let label = |t: &TodoFile| format::label_for_in(t, active, cfg.soon_days);
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+
+    match fmt {
+        OutputFormat::Table => print_table(todos, label, columns, &mut out)?,
+        OutputFormat::Json => {
+            let recs: Vec<TodoRecord> = todos.iter().map(|t| TodoRecord::new(t, label(t))).collect();
+            serde_json::to_writer_pretty(&mut out, &recs)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Ndjson => {
+            for t in todos {
+                serde_json::to_writer(&mut out, &TodoRecord::new(t, label(t)))?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let (sep, field): (&str, fn(&str) -> String) = if fmt == OutputFormat::Csv {
+                (",", csv_field)
+            } else {
+                ("\t", tsv_field)
+            };
+            writeln!(out, "{}", DELIMITED_COLUMNS.join(sep))?;
+            for t in todos {
+                let row: Vec<String> = delimited_values(t, label(t)).iter().map(|v| field(v)).collect();
+                writeln!(out, "{}", row.join(sep))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 列幅は各列の最大表示幅（全角は 2 桁）。title だけは端末幅の残りに合わせて切り詰める
+/// （端末幅が分からない＝パイプ先では切り詰めない）
+fn print_table(
+    todos: &[TodoFile],
+    label: impl Fn(&TodoFile) -> format::Label,
+    columns: &[Column],
+    out: &mut impl Write,
+) -> Result<()> {
+    let enable_color = std::env::var("NO_COLOR").is_err();
+
+    let rows: Vec<(format::Label, Vec<String>)> = todos
+        .iter()
+        .map(|t| {
+            let l = label(t);
+            (l, columns.iter().map(|c| cell(t, l, *c)).collect())
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            // 全行で空の列は 0 のまま（下で詰める）。min_width は中身がある列にだけ効かせる
+            let w = rows.iter().map(|(_, r)| format::width(&r[i])).max().unwrap_or(0);
+            if w == 0 { 0 } else { w.max(c.min_width()) }
+        })
+        .collect();
+    if let (Some(ti), Some(term)) = (columns.iter().position(|c| *c == Column::Title), terminal_width()) {
+        let others: usize = widths.iter().enumerate().filter(|(i, _)| *i != ti).map(|(_, w)| w).sum();
+        let gaps = widths.iter().filter(|w| **w > 0).count().saturating_sub(1);
+        let room = term.saturating_sub(others + gaps).max(TITLE_MIN_WIDTH);
+        widths[ti] = widths[ti].min(room);
+    }
+
+    for (l, cells) in rows {
+        let mut line = String::new();
+        for (i, (c, text)) in columns.iter().zip(&cells).enumerate() {
+            let w = widths[i];
+            // 全行で空の列（clock を使っていない等）は詰める
+            if w == 0 {
+                continue;
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            if *c == Column::Label {
+                // 色のエスケープは幅に数えない
+                line.push_str(&format::label_colored(l, enable_color));
+                line.push_str(&" ".repeat(w.saturating_sub(format::width(text))));
+            } else {
+                line.push_str(&format::pad(text, w));
+            }
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+fn cell(t: &TodoFile, l: format::Label, c: Column) -> String {
+    let fm = &t.fm;
+    match c {
+        Column::Label => format::label_str(l).to_string(),
+        Column::Due => format::due_display(t),
+        Column::Importance => format!("[{}]", fm.importance),
+        Column::Subtasks => {
+            let (done, total) = subtask::progress(&t.body);
+            if total == 0 { "".to_string() } else { format!("{}/{}", done, total) }
+        }
+        Column::Clock => {
+            let secs = clock::total_seconds(t);
+            match clock::running(t) {
+                Some(_) => format!("▶{}", clock::fmt_duration(secs)),
+                None if secs > 0 => clock::fmt_duration(secs),
+                None => String::new(),
+            }
+        }
+        Column::Pomodoros => if fm.pomodoros == 0 { String::new() } else { format!("🍅{}", fm.pomodoros) },
+        Column::Id => t.short_id(),
+        Column::Title => fm.title.clone(),
+        Column::Tags => if fm.tags.is_empty() { "".to_string() } else { format!("({})", fm.tags.join(",")) },
+        Column::Status => fm.status.as_str().to_string(),
+        Column::Created => local_date(&fm.created_at),
+        Column::Updated => local_date(&fm.updated_at),
+        Column::Repeat => fm.repeat.clone().unwrap_or_default(),
+        Column::Path => t.path.display().to_string(),
+    }
+}
+
+fn local_date(s: &str) -> String {
+    DateTime::parse_from_rfc3339(s)
+        .map(|d| d.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|_| s.to_string())
+}
+
+/// stdout が端末ならその幅、そうでなければ $COLUMNS（どちらも無ければ None）
+fn terminal_width() -> Option<usize> {
+    if std::io::stdout().is_terminal() {
+        if let Ok((w, _)) = crossterm::terminal::size() {
+            return Some(w as usize);
+        }
+    }
+    std::env::var("COLUMNS").ok()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn todo(id: &str, body: &str) -> TodoFile {
+        let text = format!("---\nid: {}\ntitle: t\nstatus: todo\nimportance: 3\ncreated_at: {}\nupdated_at: {}\n---\n{}", id, id, id, body);
+        crate::frontmatter::parse_todo_file(PathBuf::from(format!("/tmp/{}.md", id)), &text).unwrap()
+    }
+
+    fn table(todos: &[TodoFile], columns: &[Column]) -> String {
+        let mut out = Vec::new();
+        print_table(todos, |_| format::Label::None, columns, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn empty_column_collapses_despite_min_width() {
+        let todos = [todo("2026-10-17T10:00:00+09:00", "")];
+        let out = table(&todos, &[Column::Subtasks, Column::Id]);
+        assert_eq!(out, "2026-10-17T1\n");
+    }
+
+    #[test]
+    fn min_width_applies_once_a_cell_is_filled() {
+        let todos = [todo("2026-10-17T10:00:00+09:00", "## サブタスク\n- [x] a\n"), todo("2026-10-17T11:00:00+09:00", "")];
+        let out = table(&todos, &[Column::Subtasks, Column::Id]);
+        assert_eq!(out, "1/1   2026-10-17T1\n      2026-10-17T1\n");
+    }
+}
@@ -0,0 +1,228 @@
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Print,
+    terminal::{self, ClearType},
+};
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::format;
+
+/// 候補1件（表示文字列 + プレビュー/Ctrl-O 用のファイル。ファイルを持たない候補は None）
+pub struct Candidate {
+    pub display: String,
+    pub path: Option<PathBuf>,
+}
+
+/// fzf が無いときの内蔵ピッカー
+/// - 入力するたびに絞り込み（空白区切りの各語がすべて曖昧一致するもの、スコア順）
+/// - 右側にファイルのプレビュー
+/// - Enter 確定 / Esc・Ctrl-C 取り消し / Ctrl-O で $EDITOR
+///
+/// 描画は stderr に行う（stdout がパイプでも使える）。端末でなければ None。
+pub fn pick(prompt: &str, items: &[Candidate], editor: &str) -> Option<usize> {
+    if items.is_empty() || !std::io::stderr().is_terminal() {
+        return None;
+    }
+    let mut state = Picker { prompt, items, query: String::new(), hits: Vec::new(), cursor: 0, scroll: 0, previews: HashMap::new() };
+    state.refilter();
+
+    enter().ok()?;
+    let res = state.run(editor);
+    leave();
+    res.ok().flatten()
+}
+
+fn enter() -> Result<()> {
+    terminal::enable_raw_mode()?;
+    execute!(std::io::stderr(), terminal::EnterAlternateScreen, terminal::DisableLineWrap)?;
+    Ok(())
+}
+
+fn leave() {
+    let _ = execute!(std::io::stderr(), terminal::EnableLineWrap, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
+struct Picker<'a> {
+    prompt: &'a str,
+    items: &'a [Candidate],
+    query: String,
+    /// (items の index, score) をスコア順に
+    hits: Vec<(usize, i64)>,
+    cursor: usize,
+    scroll: usize,
+    previews: HashMap<usize, Vec<String>>,
+}
+
+impl Picker<'_> {
+    fn run(&mut self, editor: &str) -> Result<Option<usize>> {
+        loop {
+            self.draw()?;
+            let Event::Key(key) = event::read()? else { continue; };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Char('c') | KeyCode::Char('g') if ctrl => return Ok(None),
+                KeyCode::Enter => return Ok(self.hits.get(self.cursor).map(|(i, _)| *i)),
+                KeyCode::Down => self.move_by(1),
+                KeyCode::Char('n') | KeyCode::Char('j') if ctrl => self.move_by(1),
+                KeyCode::Up => self.move_by(-1),
+                KeyCode::Char('p') | KeyCode::Char('k') if ctrl => self.move_by(-1),
+                KeyCode::PageDown => self.move_by(10),
+                KeyCode::PageUp => self.move_by(-10),
+                KeyCode::Char('u') if ctrl => {
+                    self.query.clear();
+                    self.refilter();
+                }
+                KeyCode::Char('o') if ctrl => {
+                    let hit = self.hits.get(self.cursor).map(|(i, _)| *i);
+                    if let Some((i, Some(path))) = hit.map(|i| (i, self.items[i].path.clone())) {
+                        leave();
+                        let _ = Command::new(editor).arg(&path).status();
+                        enter()?;
+                        self.previews.remove(&i);
+                    }
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.refilter();
+                }
+                KeyCode::Char(c) if !ctrl => {
+                    self.query.push(c);
+                    self.refilter();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn refilter(&mut self) {
+        let terms: Vec<String> = self.query.split_whitespace().map(str::to_lowercase).collect();
+        self.hits = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| {
+                let text = c.display.to_lowercase();
+                let mut total = 0;
+                for t in &terms {
+                    total += score(t, &text)?;
+                }
+                Some((i, total))
+            })
+            .collect();
+        self.hits.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.hits.is_empty() {
+            return;
+        }
+        self.cursor = (self.cursor as isize + delta).clamp(0, self.hits.len() as isize - 1) as usize;
+    }
+
+    fn preview(&mut self, i: usize) -> &[String] {
+        let Some(path) = &self.items[i].path else { return &[]; };
+        self.previews.entry(i).or_insert_with(|| {
+            std::fs::read_to_string(path)
+                .map(|s| s.lines().take(200).map(|l| l.replace('\t', "    ")).collect())
+                .unwrap_or_else(|e| vec![format!("(cannot read: {})", e)])
+        })
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let (w, h) = terminal::size()?;
+        let (w, h) = (w as usize, h as usize);
+        let list_h = h.saturating_sub(2);
+        let show_preview = w >= 80;
+        let list_w = if show_preview { w * 2 / 5 } else { w };
+        let prev_w = w.saturating_sub(list_w + 3);
+
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if list_h > 0 && self.cursor >= self.scroll + list_h {
+            self.scroll = self.cursor + 1 - list_h;
+        }
+
+        let preview: Vec<String> = match self.hits.get(self.cursor).map(|(i, _)| *i) {
+            Some(i) if show_preview => self.preview(i).to_vec(),
+            _ => Vec::new(),
+        };
+
+        let mut out = std::io::stderr().lock();
+        queue!(
+            out,
+            cursor::MoveTo(0, 0),
+            Print(format::truncate(&format!("{}{}", self.prompt, self.query), w)),
+            terminal::Clear(ClearType::UntilNewLine),
+            cursor::MoveTo(0, 1),
+            Print(format!("\x1b[90m  {}/{}\x1b[0m", self.hits.len(), self.items.len())),
+            terminal::Clear(ClearType::UntilNewLine)
+        )?;
+
+        for y in 0..list_h {
+            let pos = self.scroll + y;
+            let mut line = match self.hits.get(pos) {
+                Some((i, _)) => {
+                    let text = format::pad(&format!("  {}", self.items[*i].display), list_w);
+                    if pos == self.cursor { format!("\x1b[7m{}\x1b[0m", text) } else { text }
+                }
+                None => " ".repeat(list_w),
+            };
+            if show_preview {
+                line.push_str(" │ ");
+                if let Some(p) = preview.get(y) {
+                    line.push_str(&format::truncate(p, prev_w));
+                }
+            }
+            queue!(out, cursor::MoveTo(0, (y + 2) as u16), Print(line), terminal::Clear(ClearType::UntilNewLine))?;
+        }
+
+        let col = format::width(self.prompt) + format::width(&self.query);
+        queue!(out, cursor::MoveTo(col.min(w.saturating_sub(1)) as u16, 0), cursor::Show)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// 曖昧一致のスコア（一致しなければ None）
+/// - query の文字が text に順番通り現れること
+/// - 連続一致・単語の先頭での一致を加点、間が空くほど・開始が遅いほど減点
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut pos = 0;
+    let mut prev: Option<usize> = None;
+    let mut first = None;
+    let mut total: i64 = 0;
+
+    for qc in query.chars() {
+        let found = (pos..chars.len()).find(|&i| chars[i] == qc)?;
+        total += 10;
+        if let Some(p) = prev {
+            if found == p + 1 {
+                total += 8;
+            } else {
+                total -= ((found - p - 1) as i64).min(10);
+            }
+        }
+        if found == 0 || !chars[found - 1].is_alphanumeric() {
+            total += 6;
+        }
+        first.get_or_insert(found);
+        prev = Some(found);
+        pos = found + 1;
+    }
+    total -= (first.unwrap_or(0) as i64).min(20) / 2;
+    Some(total)
+}
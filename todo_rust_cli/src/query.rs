@@ -0,0 +1,752 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate};
+use regex::{Regex, RegexBuilder};
+
+use crate::model::{Status, TodoFile};
+use crate::{due, store};
+
+/// `todo list --where` の式
+///
+/// ```text
+/// expr   := and (("or" | "||" | "|") and)*
+/// and    := unary (("and" | "&&" | "&" | ",")? unary)*      並べるだけでも AND
+/// unary  := ("not" | "!" | "-") unary | "(" expr ")" | cmp | word
+/// cmp    := field op value
+/// op     := = == != > >= < <= : ~ !~ in all
+/// value  := word | "quoted" | 'quoted' | /regex/ | "(" value ("," value)* ")" | none
+/// ```
+///
+/// - 文字列（id title status repeat restored_from）：`=` `!=`（大文字小文字無視）、`:` 部分一致、`~` 正規表現、`in (a, b)`
+/// - importance：`=` `!=` `>` `>=` `<` `<=`、`in (4, 5)`
+/// - 日付（due created_at updated_at done_at）：比較演算子。値は `--due` と同じ書式（today, +7d, eow, 2026-01-10 ...）
+///   過去は `-7d` / `7d ago` / `3日前`。日付だけの値なら日単位で比べる。`= none` / `!= none` で有無
+/// - 集合（tags blocked_by）：`:` / `=` その要素を含む、`!=` 含まない、`~` どれかが一致、`in (a, b)` どれかを含む、
+///   `all (a, b)` 全部含む、`= none` 空
+/// - text / body：`:` 部分一致、`~` 正規表現（text はタイトル+本文）
+/// - 演算子の無い単語は text の部分一致。ただし status 名（todo doing waiting done canceled）だけは `status=…`
+///   （`not waiting` は waiting 以外。その語を本文から探すなら `"waiting"`）
+pub struct Query {
+    pred: Pred,
+}
+
+type Pred = Box<dyn Fn(&TodoFile) -> bool>;
+
+impl Query {
+    pub fn matches(&self, t: &TodoFile) -> bool {
+        (self.pred)(t)
+    }
+}
+
+pub fn parse(src: &str) -> Result<Query> {
+    let tokens = tokenize(src)?;
+    let mut p = Parser { src, tokens, pos: 0 };
+    if p.tokens.is_empty() {
+        return Ok(Query { pred: Box::new(|_| true) });
+    }
+    let pred = p.parse_or()?;
+    if let Some(t) = p.peek() {
+        return Err(p.error_at(t, "unexpected token (missing 'and' / 'or'?)"));
+    }
+    Ok(Query { pred })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Word,
+    Quoted,
+    Regex,
+    LParen,
+    RParen,
+    Comma,
+    Op,
+    And,
+    Or,
+    Not,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: Kind,
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+const OPS: [&str; 11] = [">=", "<=", "!=", "==", "!~", "&&", "||", "=", ">", "<", "~"];
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let mut out = Vec::new();
+    let mut it = src.char_indices().peekable();
+
+    while let Some(&(start, c)) = it.peek() {
+        if c.is_whitespace() {
+            it.next();
+            continue;
+        }
+        let rest = &src[start..];
+
+        let single = match c {
+            '(' => Some(Kind::LParen),
+            ')' => Some(Kind::RParen),
+            ',' => Some(Kind::Comma),
+            ':' => Some(Kind::Op),
+            _ => None,
+        };
+        if let Some(kind) = single {
+            it.next();
+            out.push(Token { kind, text: c.to_string(), start, end: start + 1 });
+            continue;
+        }
+
+        if let Some(op) = OPS.iter().find(|op| rest.starts_with(**op)) {
+            for _ in 0..op.len() {
+                it.next();
+            }
+            let kind = match *op {
+                "&&" => Kind::And,
+                "||" => Kind::Or,
+                _ => Kind::Op,
+            };
+            out.push(Token { kind, text: op.to_string(), start, end: start + op.len() });
+            continue;
+        }
+        if c == '!' || c == '|' || c == '&' {
+            it.next();
+            let kind = match c {
+                '!' => Kind::Not,
+                '|' => Kind::Or,
+                _ => Kind::And,
+            };
+            out.push(Token { kind, text: c.to_string(), start, end: start + 1 });
+            continue;
+        }
+        // -word / -( は否定
+        if c == '-' {
+            let next = rest[1..].chars().next();
+            if next.is_some_and(|n| n.is_alphabetic() || n == '(') {
+                it.next();
+                out.push(Token { kind: Kind::Not, text: "-".to_string(), start, end: start + 1 });
+                continue;
+            }
+        }
+
+        if c == '"' || c == '\'' || c == '/' {
+            it.next();
+            let mut text = String::new();
+            let mut closed = None;
+            while let Some((i, ch)) = it.next() {
+                if ch == '\\' && c != '/' {
+                    if let Some((_, esc)) = it.next() {
+                        text.push(esc);
+                    }
+                    continue;
+                }
+                if ch == '\\' && c == '/' {
+                    // 正規表現では \/ だけ外し、他のエスケープはそのまま渡す
+                    if let Some(&(_, '/')) = it.peek() {
+                        it.next();
+                        text.push('/');
+                    } else {
+                        text.push('\\');
+                    }
+                    continue;
+                }
+                if ch == c {
+                    closed = Some(i + 1);
+                    break;
+                }
+                text.push(ch);
+            }
+            let Some(end) = closed else {
+                return Err(caret(src, start, src.len(), &format!("unterminated {}", if c == '/' { "regex" } else { "string" })));
+            };
+            let kind = if c == '/' { Kind::Regex } else { Kind::Quoted };
+            out.push(Token { kind, text, start, end });
+            continue;
+        }
+
+        let mut end = start;
+        while let Some(&(i, ch)) = it.peek() {
+            if ch.is_whitespace() || "(),:=!<>~\"'|&".contains(ch) {
+                break;
+            }
+            end = i + ch.len_utf8();
+            it.next();
+        }
+        let text = src[start..end].to_string();
+        let kind = match text.to_lowercase().as_str() {
+            "and" => Kind::And,
+            "or" => Kind::Or,
+            "not" => Kind::Not,
+            _ => Kind::Word,
+        };
+        out.push(Token { kind, text, start, end });
+    }
+    Ok(out)
+}
+
+/// 式の下に ^^^ を付けたエラー
+fn caret(src: &str, start: usize, end: usize, msg: &str) -> anyhow::Error {
+    let col = src[..start].chars().count();
+    let width = src[start..end].chars().count().max(1);
+    anyhow!("invalid --where: {}\n  {}\n  {}{}", msg, src, " ".repeat(col), "^".repeat(width))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Id,
+    Title,
+    Status,
+    Repeat,
+    RestoredFrom,
+    Importance,
+    Due,
+    CreatedAt,
+    UpdatedAt,
+    DoneAt,
+    Tags,
+    BlockedBy,
+    Text,
+    Body,
+}
+
+fn field_of(name: &str) -> Option<Field> {
+    Some(match name.to_lowercase().as_str() {
+        "id" => Field::Id,
+        "title" => Field::Title,
+        "status" => Field::Status,
+        "repeat" => Field::Repeat,
+        "restored_from" => Field::RestoredFrom,
+        "importance" | "imp" => Field::Importance,
+        "due" => Field::Due,
+        "created_at" | "created" => Field::CreatedAt,
+        "updated_at" | "updated" => Field::UpdatedAt,
+        "done_at" => Field::DoneAt,
+        "tags" | "tag" => Field::Tags,
+        "blocked_by" => Field::BlockedBy,
+        "text" => Field::Text,
+        "body" => Field::Body,
+        _ => return None,
+    })
+}
+
+const FIELDS: &str = "id, title, status, repeat, restored_from, importance, due, created_at, updated_at, done_at, tags, blocked_by, text, body";
+
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+/// 値（単語/文字列/正規表現/リスト）と、その位置
+struct Value {
+    items: Vec<Token>,
+    regex: Option<Token>,
+    start: usize,
+    end: usize,
+}
+
+impl Value {
+    fn single(&self) -> Option<&Token> {
+        if self.items.len() == 1 { self.items.first() } else { None }
+    }
+
+    fn is_none(&self) -> bool {
+        self.single().is_some_and(|t| t.kind == Kind::Word && matches!(t.text.to_lowercase().as_str(), "none" | "null"))
+    }
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn error_at(&self, t: &Token, msg: &str) -> anyhow::Error {
+        caret(self.src, t.start, t.end, msg)
+    }
+
+    fn error_end(&self, msg: &str) -> anyhow::Error {
+        caret(self.src, self.src.len(), self.src.len(), msg)
+    }
+
+    fn parse_or(&mut self) -> Result<Pred> {
+        let mut left = self.parse_and()?;
+        while self.peek().is_some_and(|t| t.kind == Kind::Or) {
+            self.next();
+            let right = self.parse_and()?;
+            left = Box::new(move |t| left(t) || right(t));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Pred> {
+        let mut left = self.parse_unary()?;
+        loop {
+            match self.peek().map(|t| t.kind.clone()) {
+                Some(Kind::And) | Some(Kind::Comma) => {
+                    self.next();
+                }
+                Some(Kind::Word) | Some(Kind::Quoted) | Some(Kind::Not) | Some(Kind::LParen) => {}
+                _ => break,
+            }
+            let right = self.parse_unary()?;
+            left = Box::new(move |t| left(t) && right(t));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Pred> {
+        let Some(tok) = self.next() else {
+            return Err(self.error_end("expression expected"));
+        };
+        match tok.kind {
+            Kind::Not => {
+                let inner = self.parse_unary()?;
+                Ok(Box::new(move |t| !inner(t)))
+            }
+            Kind::LParen => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(t) if t.kind == Kind::RParen => Ok(inner),
+                    Some(t) => Err(self.error_at(&t, "')' expected")),
+                    None => Err(self.error_at(&tok, "unclosed '('")),
+                }
+            }
+            Kind::Word | Kind::Quoted => {
+                let op = self.peek().filter(|t| {
+                    t.kind == Kind::Op || (t.kind == Kind::Word && matches!(t.text.to_lowercase().as_str(), "in" | "all"))
+                });
+                if op.is_none() || tok.kind == Kind::Quoted {
+                    if tok.kind == Kind::Word {
+                        if let Ok(status) = tok.text.parse::<Status>() {
+                            return Ok(Box::new(move |t| t.fm.status == status));
+                        }
+                    }
+                    return Ok(text_contains(Field::Text, &tok.text));
+                }
+                let Some(field) = field_of(&tok.text) else {
+                    return Err(self.error_at(&tok, &format!("unknown field '{}' (fields: {})", tok.text, FIELDS)));
+                };
+                let op = self.next().unwrap();
+                let value = self.parse_value(&op)?;
+                self.compare(field, &tok, &op, value)
+            }
+            _ => Err(self.error_at(&tok, "expression expected")),
+        }
+    }
+
+    fn parse_value(&mut self, op: &Token) -> Result<Value> {
+        let Some(tok) = self.next() else {
+            return Err(self.error_end(&format!("value expected after '{}'", op.text)));
+        };
+        match tok.kind {
+            Kind::Word => {
+                // `7d ago` / `2 weeks ago` は1つの値
+                let ago = (0..2).find(|i| {
+                    let t = self.tokens.get(self.pos + i);
+                    t.is_some_and(|t| t.kind == Kind::Word && t.text.eq_ignore_ascii_case("ago"))
+                        && self.tokens[self.pos..=self.pos + i].iter().all(|t| t.kind == Kind::Word)
+                });
+                let mut tok = tok;
+                if let Some(i) = ago {
+                    for _ in 0..=i {
+                        let w = self.next().unwrap();
+                        tok.text = format!("{} {}", tok.text, w.text);
+                        tok.end = w.end;
+                    }
+                }
+                Ok(Value { start: tok.start, end: tok.end, items: vec![tok], regex: None })
+            }
+            Kind::Quoted => Ok(Value { start: tok.start, end: tok.end, items: vec![tok], regex: None }),
+            Kind::Regex => Ok(Value { start: tok.start, end: tok.end, items: Vec::new(), regex: Some(tok) }),
+            Kind::LParen => {
+                let mut items = Vec::new();
+                loop {
+                    let Some(t) = self.next() else {
+                        return Err(self.error_at(&tok, "unclosed '('"));
+                    };
+                    match t.kind {
+                        Kind::Word | Kind::Quoted => items.push(t),
+                        Kind::Comma => {}
+                        Kind::RParen => return Ok(Value { start: tok.start, end: t.end, items, regex: None }),
+                        _ => return Err(self.error_at(&t, "list item expected")),
+                    }
+                }
+            }
+            _ => Err(self.error_at(&tok, "value expected")),
+        }
+    }
+
+    fn compare(&self, field: Field, ftok: &Token, op: &Token, value: Value) -> Result<Pred> {
+        let op_s = op.text.to_lowercase();
+        let op_s = if op_s == "==" { "=".to_string() } else { op_s };
+        let vtok = Token { kind: Kind::Word, text: String::new(), start: value.start, end: value.end };
+        let bad_op = || self.error_at(op, &format!("operator '{}' is not supported for {}", op.text, ftok.text));
+
+        let regex = || -> Result<Regex> {
+            let src = match (&value.regex, value.single()) {
+                (Some(r), _) => r.text.clone(),
+                (None, Some(t)) => t.text.clone(),
+                _ => return Err(self.error_at(&vtok, "regex expected")),
+            };
+            RegexBuilder::new(&src)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| self.error_at(&vtok, &format!("bad regex: {}", e.to_string().lines().last().unwrap_or(""))))
+        };
+        let single = || -> Result<String> {
+            match value.single() {
+                Some(t) if value.regex.is_none() => Ok(t.text.clone()),
+                _ => Err(self.error_at(&vtok, "single value expected")),
+            }
+        };
+        let list = || -> Vec<String> { value.items.iter().map(|t| t.text.to_lowercase()).collect() };
+
+        match field {
+            Field::Id | Field::Title | Field::Status | Field::Repeat | Field::RestoredFrom => {
+                let get = move |t: &TodoFile| -> Option<String> {
+                    match field {
+                        Field::Id => Some(t.fm.id.clone()),
+                        Field::Title => Some(t.fm.title.clone()),
+                        Field::Status => Some(t.fm.status.as_str().to_string()),
+                        Field::Repeat => t.fm.repeat.clone(),
+                        _ => t.fm.restored_from.clone(),
+                    }
+                };
+                if field == Field::Status && value.regex.is_none() {
+                    for v in &value.items {
+                        if !value.is_none() && v.text.parse::<Status>().is_err() {
+                            return Err(self.error_at(v, "unknown status (todo, doing, waiting, done, canceled)"));
+                        }
+                    }
+                }
+                match op_s.as_str() {
+                    "=" | "!=" if value.is_none() => {
+                        let want_none = op_s == "=";
+                        Ok(Box::new(move |t| get(t).is_none() == want_none))
+                    }
+                    "=" | "!=" => {
+                        let v = single()?.to_lowercase();
+                        let v = if field == Field::Status { v.replace("cancelled", "canceled") } else { v };
+                        let eq = op_s == "=";
+                        Ok(Box::new(move |t| get(t).map(|s| s.to_lowercase() == v).unwrap_or(false) == eq))
+                    }
+                    ":" => {
+                        let v = single()?.to_lowercase();
+                        Ok(Box::new(move |t| get(t).is_some_and(|s| s.to_lowercase().contains(&v))))
+                    }
+                    "~" | "!~" => {
+                        let re = regex()?;
+                        let want = op_s == "~";
+                        Ok(Box::new(move |t| get(t).is_some_and(|s| re.is_match(&s)) == want))
+                    }
+                    "in" => {
+                        let vs = list();
+                        Ok(Box::new(move |t| get(t).is_some_and(|s| vs.contains(&s.to_lowercase()))))
+                    }
+                    _ => Err(bad_op()),
+                }
+            }
+
+            Field::Importance => {
+                let nums = value
+                    .items
+                    .iter()
+                    .map(|v| v.text.parse::<i32>().map_err(|_| self.error_at(v, "number expected")))
+                    .collect::<Result<Vec<i32>>>()?;
+                if op_s == "in" {
+                    return Ok(Box::new(move |t| nums.contains(&t.fm.importance)));
+                }
+                let n = match nums.as_slice() {
+                    [n] => *n,
+                    _ => return Err(self.error_at(&vtok, "single number expected")),
+                };
+                let cmp = ord_op(&op_s).ok_or_else(bad_op)?;
+                Ok(Box::new(move |t| cmp(t.fm.importance.cmp(&n))))
+            }
+
+            Field::Due | Field::CreatedAt | Field::UpdatedAt | Field::DoneAt => {
+                let get = move |t: &TodoFile| -> Option<DateTime<FixedOffset>> {
+                    match field {
+                        Field::Due => t.fm.due.as_deref().and_then(store::parse_due_dt),
+                        Field::CreatedAt => DateTime::parse_from_rfc3339(&t.fm.created_at).ok(),
+                        Field::UpdatedAt => DateTime::parse_from_rfc3339(&t.fm.updated_at).ok(),
+                        _ => t.fm.done_at.as_deref().and_then(|s| DateTime::parse_from_rfc3339(s).ok()),
+                    }
+                };
+                if value.is_none() {
+                    let want_none = match op_s.as_str() {
+                        "=" => true,
+                        "!=" => false,
+                        _ => return Err(bad_op()),
+                    };
+                    return Ok(Box::new(move |t| get(t).is_none() == want_none));
+                }
+                let cmp = ord_op(&op_s).ok_or_else(bad_op)?;
+                let raw = single()?;
+                let lower = raw.trim().to_lowercase();
+                let norm = if lower.starts_with('-') || lower.ends_with("ago") || lower.ends_with('前') {
+                    due::parse_past(&raw).map(|d| d.format("%Y-%m-%d").to_string())
+                } else {
+                    due::normalize(&raw)
+                }
+                .map_err(|_| {
+                    self.error_at(&vtok, "date expected (YYYY-MM-DD, RFC3339, today, +7d, -7d, 7d ago, eow, fri ... or none)")
+                })?;
+
+                if let Ok(d) = NaiveDate::parse_from_str(&norm, "%Y-%m-%d") {
+                    // 日付だけなら日単位で比べる
+                    Ok(Box::new(move |t| get(t).is_some_and(|dt| cmp(dt.with_timezone(&Local).date_naive().cmp(&d)))))
+                } else {
+                    let at = DateTime::parse_from_rfc3339(&norm).map_err(|_| self.error_at(&vtok, "invalid date"))?;
+                    Ok(Box::new(move |t| get(t).is_some_and(|dt| cmp(dt.cmp(&at)))))
+                }
+            }
+
+            Field::Tags | Field::BlockedBy => {
+                let get = move |t: &TodoFile| -> Vec<String> {
+                    let v = if field == Field::Tags { &t.fm.tags } else { &t.fm.blocked_by };
+                    v.iter().map(|s| s.to_lowercase()).collect()
+                };
+                match op_s.as_str() {
+                    "=" | "!=" if value.is_none() => {
+                        let want_empty = op_s == "=";
+                        Ok(Box::new(move |t| get(t).is_empty() == want_empty))
+                    }
+                    ":" | "=" | "!=" => {
+                        let v = single()?.to_lowercase();
+                        let has = op_s != "!=";
+                        Ok(Box::new(move |t| get(t).contains(&v) == has))
+                    }
+                    "~" | "!~" => {
+                        let re = regex()?;
+                        let want = op_s == "~";
+                        Ok(Box::new(move |t| get(t).iter().any(|s| re.is_match(s)) == want))
+                    }
+                    "in" => {
+                        let vs = list();
+                        Ok(Box::new(move |t| get(t).iter().any(|s| vs.contains(s))))
+                    }
+                    "all" => {
+                        let vs = list();
+                        Ok(Box::new(move |t| {
+                            let have = get(t);
+                            vs.iter().all(|v| have.contains(v))
+                        }))
+                    }
+                    _ => Err(bad_op()),
+                }
+            }
+
+            Field::Text | Field::Body => match op_s.as_str() {
+                ":" | "=" => Ok(text_contains(field, &single()?)),
+                "!=" => {
+                    let p = text_contains(field, &single()?);
+                    Ok(Box::new(move |t| !p(t)))
+                }
+                "~" | "!~" => {
+                    let re = regex()?;
+                    let want = op_s == "~";
+                    Ok(Box::new(move |t| {
+                        let hit = re.is_match(&t.body) || (field == Field::Text && re.is_match(&t.fm.title));
+                        hit == want
+                    }))
+                }
+                _ => Err(bad_op()),
+            },
+        }
+    }
+}
+
+fn ord_op(op: &str) -> Option<fn(std::cmp::Ordering) -> bool> {
+    use std::cmp::Ordering::*;
+    Some(match op {
+        "=" => |o| o == Equal,
+        "!=" => |o| o != Equal,
+        ">" => |o| o == Greater,
+        ">=" => |o| o != Less,
+        "<" => |o| o == Less,
+        "<=" => |o| o != Greater,
+        _ => return None,
+    })
+}
+
+fn text_contains(field: Field, q: &str) -> Pred {
+    let q = q.to_lowercase();
+    Box::new(move |t| t.body.to_lowercase().contains(&q) || (field == Field::Text && t.fm.title.to_lowercase().contains(&q)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter::parse_todo_file;
+
+    fn todo(title: &str, edit: impl FnOnce(&mut crate::model::FrontMatter), body: &str) -> TodoFile {
+        let text = format!(
+            "---\nid: 2026-01-01T00-00-00__{}\ntitle: {}\nstatus: todo\nimportance: 3\ncreated_at: 2026-01-01T09:00:00+09:00\nupdated_at: 2026-01-02T09:00:00+09:00\n---\n{}",
+            title.to_lowercase().replace(' ', "-"),
+            title,
+            body
+        );
+        let mut t = parse_todo_file(std::path::PathBuf::from("t.md"), &text).unwrap();
+        edit(&mut t.fm);
+        t
+    }
+
+    fn tags(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn sample() -> Vec<TodoFile> {
+        vec![
+            todo(
+                "Write report",
+                |fm| {
+                    fm.tags = tags(&["work", "urgent"]);
+                    fm.importance = 5;
+                    fm.due = Some("2026-01-10".to_string());
+                },
+                "## メモ\nquarterly numbers\n",
+            ),
+            todo(
+                "Buy milk",
+                |fm| {
+                    fm.tags = tags(&["home"]);
+                    fm.status = Status::Doing;
+                },
+                "",
+            ),
+            todo(
+                "Call bank",
+                |fm| {
+                    fm.importance = 4;
+                    fm.repeat = Some("every 1m".to_string());
+                    fm.blocked_by = tags(&["2026-01-01T00-00-00__buy-milk"]);
+                },
+                "ask about fees\n",
+            ),
+        ]
+    }
+
+    /// 一致した todo のタイトル
+    fn hits(q: &str) -> Vec<String> {
+        hits_in(sample(), q)
+    }
+
+    fn hits_in(list: Vec<TodoFile>, q: &str) -> Vec<String> {
+        let q = parse(q).unwrap_or_else(|e| panic!("{}", e));
+        list.into_iter().filter(|t| q.matches(t)).map(|t| t.fm.title).collect()
+    }
+
+    /// title の todo だけ fm を書き換えた sample
+    fn sample_with(title: &str, edit: impl Fn(&mut crate::model::FrontMatter)) -> Vec<TodoFile> {
+        let mut list = sample();
+        list.iter_mut().filter(|t| t.fm.title == title).for_each(|t| edit(&mut t.fm));
+        list
+    }
+
+    fn err(q: &str) -> String {
+        parse(q).err().map(|e| e.to_string()).unwrap_or_default()
+    }
+
+    #[test]
+    fn empty_matches_everything() {
+        assert_eq!(hits("").len(), 3);
+        assert_eq!(hits("   ").len(), 3);
+    }
+
+    #[test]
+    fn strings_and_status() {
+        assert_eq!(hits("status = doing"), ["Buy milk"]);
+        assert_eq!(hits("status != todo"), ["Buy milk"]);
+        assert_eq!(hits("title : BANK"), ["Call bank"]);
+        assert_eq!(hits("title ~ /^(buy|call)/"), ["Buy milk", "Call bank"]);
+        assert_eq!(hits("status in (doing, waiting)"), ["Buy milk"]);
+        assert_eq!(hits("repeat = none"), ["Write report", "Buy milk"]);
+    }
+
+    #[test]
+    fn importance_and_dates() {
+        assert_eq!(hits("importance >= 4"), ["Write report", "Call bank"]);
+        assert_eq!(hits("imp in (3, 5)"), ["Write report", "Buy milk"]);
+        assert_eq!(hits("due < 2026-01-11"), ["Write report"]);
+        assert_eq!(hits("due = 2026-01-10"), ["Write report"]);
+        assert_eq!(hits("due = none"), ["Buy milk", "Call bank"]);
+        assert_eq!(hits("created_at <= 2026-01-01"), ["Write report", "Buy milk", "Call bank"]);
+    }
+
+    #[test]
+    fn past_relative_dates() {
+        let recent = (Local::now() - chrono::Duration::days(3)).to_rfc3339();
+        for q in ["created_at > -7d", "created > 7d ago", "created >= 1 week ago", "created_at > 7日前"] {
+            assert_eq!(hits_in(sample_with("Buy milk", |fm| fm.created_at = recent.clone()), q), ["Buy milk"], "{}", q);
+        }
+        assert!(err("created_at > 7 parsecs ago").contains("date expected"));
+    }
+
+    #[test]
+    fn bare_status_names_filter_by_status() {
+        let list = || sample_with("Call bank", |fm| fm.status = Status::Waiting);
+        assert_eq!(hits_in(list(), "not waiting"), ["Write report", "Buy milk"]);
+        assert_eq!(hits_in(list(), "doing or waiting"), ["Buy milk", "Call bank"]);
+        // 本文から探すなら引用符
+        assert_eq!(hits_in(list(), "not \"waiting\""), ["Write report", "Buy milk", "Call bank"]);
+    }
+
+    #[test]
+    fn sets() {
+        assert_eq!(hits("tags : work"), ["Write report"]);
+        assert_eq!(hits("tag != work"), ["Buy milk", "Call bank"]);
+        assert_eq!(hits("tags in (home, urgent)"), ["Write report", "Buy milk"]);
+        assert_eq!(hits("tags all (work, urgent)"), ["Write report"]);
+        assert_eq!(hits("tags all (work, home)"), Vec::<String>::new());
+        assert_eq!(hits("tags = none"), ["Call bank"]);
+        assert_eq!(hits("blocked_by ~ /milk/"), ["Call bank"]);
+    }
+
+    #[test]
+    fn text_and_bare_words() {
+        assert_eq!(hits("quarterly"), ["Write report"]);
+        assert_eq!(hits("\"ask about\""), ["Call bank"]);
+        assert_eq!(hits("body : fees"), ["Call bank"]);
+        // text はタイトルも見るが body は見ない
+        assert_eq!(hits("text : milk"), ["Buy milk"]);
+        assert_eq!(hits("body : milk"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn boolean_precedence() {
+        // and は or より強い
+        assert_eq!(hits("status = doing or tags : work and importance = 5"), ["Write report", "Buy milk"]);
+        assert_eq!(hits("(status = doing or tags : work) and importance = 5"), ["Write report"]);
+        // 並べるだけで AND、, や && も AND
+        assert_eq!(hits("tags : work importance = 5"), ["Write report"]);
+        assert_eq!(hits("tags : work, importance = 3"), Vec::<String>::new());
+        assert_eq!(hits("imp >= 4 && !tags : work"), ["Call bank"]);
+        assert_eq!(hits("-tags : work -status = doing"), ["Call bank"]);
+        assert_eq!(hits("not (imp >= 4) || repeat : 1m"), ["Buy milk", "Call bank"]);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let e = err("colour = red");
+        assert!(e.contains("unknown field 'colour'"), "{}", e);
+        assert!(e.contains("\n  ^^^^^^"), "{}", e);
+
+        assert!(err("status = finished").contains("unknown status"));
+        assert!(err("importance > high").contains("number expected"));
+        assert!(err("due < someday").contains("date expected"));
+        assert!(err("tags > 3").contains("operator '>' is not supported"));
+        assert!(err("title ~ /(/").contains("bad regex"));
+        assert!(err("title = \"oops").contains("unterminated string"));
+        assert!(err("(status = todo").contains("unclosed '('"));
+        assert!(err("status =").contains("value expected"));
+        assert!(err("status = todo )").contains("unexpected token"));
+    }
+}
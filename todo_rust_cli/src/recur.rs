@@ -0,0 +1,309 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, Weekday};
+
+use crate::store::parse_due_dt;
+
+/// `repeat` フィールドの解釈結果
+/// - `every 1w` / `every 3d` / `every 2m` / `every 1y`
+/// - `daily` / `weekly` / `monthly` / `yearly`
+/// - `weekdays`（月〜金）
+/// - `monthly on 25` / `monthly on last`
+/// - `weekly on mon,thu`（`every mon,thu` も可）
+/// - 月・年単位には `on 31` / `on last` で基準の日を付けられる（`every 1m on 31`）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repeat {
+    Days(u32),
+    Weeks(u32),
+    /// 基準の日（None なら due の日）。月末を超える月は月末に丸める
+    Months(u32, Option<MonthDay>),
+    Years(u32, Option<MonthDay>),
+    Weekdays,
+    MonthlyOn(MonthDay),
+    WeeklyOn(Vec<Weekday>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonthDay {
+    Day(u32),
+    Last,
+}
+
+impl std::str::FromStr for Repeat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let norm = s.trim().to_lowercase();
+        let words: Vec<&str> = norm.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["daily"] => Ok(Repeat::Days(1)),
+            ["weekly"] => Ok(Repeat::Weeks(1)),
+            ["monthly"] => Ok(Repeat::Months(1, None)),
+            ["yearly"] | ["annually"] => Ok(Repeat::Years(1, None)),
+            ["weekdays"] => Ok(Repeat::Weekdays),
+            ["every", "day"] => Ok(Repeat::Days(1)),
+            ["every", "week"] => Ok(Repeat::Weeks(1)),
+            ["every", "month"] => Ok(Repeat::Months(1, None)),
+            ["every", "year"] => Ok(Repeat::Years(1, None)),
+            ["every", "weekday"] => Ok(Repeat::Weekdays),
+            ["every", n] if n.starts_with(|c: char| c.is_ascii_digit()) => {
+                parse_interval(n).ok_or_else(|| invalid(s))
+            }
+            ["every", days] => parse_weekdays(days).map(Repeat::WeeklyOn).ok_or_else(|| invalid(s)),
+            ["monthly", "on", d] => parse_month_day(d).map(Repeat::MonthlyOn).ok_or_else(|| invalid(s)),
+            ["weekly", "on", days] => parse_weekdays(days).map(Repeat::WeeklyOn).ok_or_else(|| invalid(s)),
+            [head @ .., "on", d] => match (head.join(" ").parse::<Repeat>(), parse_month_day(d)) {
+                (Ok(Repeat::Months(n, None)), Some(md)) => Ok(Repeat::Months(n, Some(md))),
+                (Ok(Repeat::Years(n, None)), Some(md)) => Ok(Repeat::Years(n, Some(md))),
+                _ => Err(invalid(s)),
+            },
+            _ => Err(invalid(s)),
+        }
+    }
+}
+
+fn invalid(s: &str) -> anyhow::Error {
+    anyhow!(
+        "invalid repeat: {} (use like 'every 1w', 'monthly on 25', 'weekdays', 'weekly on mon,thu')",
+        s.trim()
+    )
+}
+
+fn parse_interval(s: &str) -> Option<Repeat> {
+    // 末尾が「週」のような多バイト文字でもよいように、単位は文字で切り出す
+    let (i, unit) = s.char_indices().last()?;
+    let n: u32 = s[..i].parse().ok()?;
+    if n == 0 {
+        return None;
+    }
+    match unit {
+        'd' => Some(Repeat::Days(n)),
+        'w' => Some(Repeat::Weeks(n)),
+        'm' => Some(Repeat::Months(n, None)),
+        'y' => Some(Repeat::Years(n, None)),
+        _ => None,
+    }
+}
+
+/// `25` / `last`
+fn parse_month_day(s: &str) -> Option<MonthDay> {
+    if s == "last" {
+        return Some(MonthDay::Last);
+    }
+    let d: u32 = s.parse().ok()?;
+    (1..=31).contains(&d).then_some(MonthDay::Day(d))
+}
+
+fn parse_weekdays(s: &str) -> Option<Vec<Weekday>> {
+    let mut out = Vec::new();
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let wd: Weekday = part.parse().ok()?;
+        if !out.contains(&wd) {
+            out.push(wd);
+        }
+    }
+    if out.is_empty() { None } else { Some(out) }
+}
+
+impl Repeat {
+    /// 月・年単位で基準の日が無ければ base の日にする
+    fn anchored(&self, base: NaiveDate) -> Repeat {
+        match self {
+            Repeat::Months(n, None) => Repeat::Months(*n, Some(MonthDay::Day(base.day()))),
+            Repeat::Years(n, None) => Repeat::Years(*n, Some(MonthDay::Day(base.day()))),
+            other => other.clone(),
+        }
+    }
+
+    /// base より後の最初の発生日
+    pub fn step(&self, base: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Repeat::Days(n) => base.checked_add_signed(Duration::days(*n as i64)),
+            Repeat::Weeks(n) => base.checked_add_signed(Duration::weeks(*n as i64)),
+            // 前回の日ではなく基準の日から数える（1/31 → 2/28 → 3/31）
+            Repeat::Months(n, day) => add_months(base, *n, *day),
+            Repeat::Years(n, day) => add_months(base, n * 12, *day),
+            Repeat::Weekdays => {
+                let mut d = base.succ_opt()?;
+                while matches!(d.weekday(), Weekday::Sat | Weekday::Sun) {
+                    d = d.succ_opt()?;
+                }
+                Some(d)
+            }
+            Repeat::WeeklyOn(days) => {
+                let mut d = base.succ_opt()?;
+                for _ in 0..7 {
+                    if days.contains(&d.weekday()) {
+                        return Some(d);
+                    }
+                    d = d.succ_opt()?;
+                }
+                None
+            }
+            Repeat::MonthlyOn(md) => {
+                let this_month = day_in_month(base.year(), base.month(), *md)?;
+                if this_month > base {
+                    return Some(this_month);
+                }
+                let next = base.with_day(1)?.checked_add_months(Months::new(1))?;
+                day_in_month(next.year(), next.month(), *md)
+            }
+        }
+    }
+}
+
+/// n か月後の day 日（None なら base と同じ日。月末を超える場合は月末に丸める）
+fn add_months(base: NaiveDate, n: u32, day: Option<MonthDay>) -> Option<NaiveDate> {
+    let month = base.with_day(1)?.checked_add_months(Months::new(n))?;
+    day_in_month(month.year(), month.month(), day.unwrap_or(MonthDay::Day(base.day())))
+}
+
+/// 指定月の day 日（月末を超える場合は月末に丸める）
+fn day_in_month(year: i32, month: u32, md: MonthDay) -> Option<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+    match md {
+        MonthDay::Last => Some(last),
+        MonthDay::Day(d) => Some(if d > last.day() { last } else { first.with_day(d)? }),
+    }
+}
+
+/// 次回の due を計算する
+/// - 基準は現在の due（無ければ今日）
+/// - 今日以前にしかならない場合は今日より後になるまで進める
+/// - YYYY-MM-DD は日付のまま、RFC3339 は時刻とオフセットを維持
+pub fn next_due(repeat: &Repeat, due: Option<&str>) -> Option<String> {
+    let today = Local::now().date_naive();
+
+    let parsed = due.and_then(parse_due_dt);
+    let date_only = due.map(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()).unwrap_or(true);
+
+    let base = parsed.map(|dt| dt.date_naive()).unwrap_or(today);
+    // 追いつくまで進める間も最初の due の日を基準にする
+    let repeat = &repeat.anchored(base);
+    let mut next = repeat.step(base)?;
+    for _ in 0..10_000 {
+        if next > today {
+            break;
+        }
+        next = repeat.step(next)?;
+    }
+
+    if date_only || parsed.is_none() {
+        return Some(next.format("%Y-%m-%d").to_string());
+    }
+
+    let dt = parsed?;
+    let naive = next.and_time(dt.time());
+    let next_dt: DateTime<_> = naive.and_local_timezone(*dt.offset()).single()?;
+    Some(next_dt.to_rfc3339())
+}
+
+/// 次回分に引き継ぐ repeat
+/// 月・年単位で due が 29 日以降なら、丸められた次回分からも元の日に戻れるよう `on N` を付ける
+pub fn next_spec(spec: &str, repeat: &Repeat, due: Option<&str>) -> String {
+    let day = due.and_then(parse_due_dt).map(|dt| dt.day()).filter(|d| *d > 28);
+    match (repeat, day) {
+        (Repeat::Months(_, None) | Repeat::Years(_, None), Some(d)) => format!("{} on {}", spec.trim(), d),
+        _ => spec.to_string(),
+    }
+}
+
+/// 次回分の本文：チェックを外し、ログ節は空にする
+pub fn fresh_body(body: &str) -> String {
+    let mut out = String::new();
+    let mut in_log = false;
+
+    for line in body.lines() {
+        if line.starts_with("## ") {
+            in_log = line.trim_end() == "## ログ";
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+        if in_log {
+            continue;
+        }
+        let trimmed = line.trim_start();
+        let unchecked = if let Some(rest) = trimmed.strip_prefix("- [x]").or_else(|| trimmed.strip_prefix("- [X]")) {
+            let indent = &line[..line.len() - trimmed.len()];
+            format!("{}- [ ]{}", indent, rest)
+        } else {
+            line.to_string()
+        };
+        out.push_str(&unchecked);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_intervals() {
+        assert_eq!("every 3d".parse::<Repeat>().unwrap(), Repeat::Days(3));
+        assert_eq!("every 1w".parse::<Repeat>().unwrap(), Repeat::Weeks(1));
+        assert_eq!("Every 2M".parse::<Repeat>().unwrap(), Repeat::Months(2, None));
+        assert_eq!("every 1y".parse::<Repeat>().unwrap(), Repeat::Years(1, None));
+        assert_eq!("every 1m on 31".parse::<Repeat>().unwrap(), Repeat::Months(1, Some(MonthDay::Day(31))));
+        assert_eq!("yearly on last".parse::<Repeat>().unwrap(), Repeat::Years(1, Some(MonthDay::Last)));
+        assert!("every 1w on 3".parse::<Repeat>().is_err());
+        assert!("every 1m on 32".parse::<Repeat>().is_err());
+        assert!("every 0d".parse::<Repeat>().is_err());
+        assert!("every 1x".parse::<Repeat>().is_err());
+    }
+
+    #[test]
+    fn multibyte_unit_is_an_error_not_a_panic() {
+        assert!("every 1週".parse::<Repeat>().is_err());
+        assert!("every 1日".parse::<Repeat>().is_err());
+        assert!("every 12ヶ月".parse::<Repeat>().is_err());
+    }
+
+    #[test]
+    fn monthly_on_clamps_to_month_end() {
+        let r: Repeat = "monthly on 31".parse().unwrap();
+        let base = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+        assert_eq!(r.step(base), NaiveDate::from_ymd_opt(2026, 2, 28));
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn monthly_interval_keeps_the_31st() {
+        // 1つの todo の中で追いつくまで進めても、丸めた日を次の基準にしない
+        let r = Repeat::Months(1, None).anchored(date(2026, 1, 31));
+        let mut d = date(2026, 1, 31);
+        let mut got = Vec::new();
+        for _ in 0..4 {
+            d = r.step(d).unwrap();
+            got.push(d);
+        }
+        assert_eq!(got, vec![date(2026, 2, 28), date(2026, 3, 31), date(2026, 4, 30), date(2026, 5, 31)]);
+    }
+
+    #[test]
+    fn next_spec_carries_the_day_to_spawned_todos() {
+        let r: Repeat = "every 1m".parse().unwrap();
+        let spec = next_spec("every 1m", &r, Some("2026-01-31"));
+        assert_eq!(spec, "every 1m on 31");
+        // 2/28 になった次回分を done にしても 3/31 に戻る
+        let r: Repeat = spec.parse().unwrap();
+        assert_eq!(r.step(date(2026, 2, 28)), Some(date(2026, 3, 31)));
+        assert_eq!(next_spec(&spec, &r, Some("2026-02-28")), spec);
+
+        assert_eq!(next_spec("every 1m", &"every 1m".parse().unwrap(), Some("2026-01-15")), "every 1m");
+        assert_eq!(next_spec("yearly", &"yearly".parse().unwrap(), Some("2028-02-29")), "yearly on 29");
+    }
+
+    #[test]
+    fn yearly_on_feb_29_comes_back_in_leap_years() {
+        let r: Repeat = "yearly on 29".parse().unwrap();
+        assert_eq!(r.step(date(2028, 2, 29)), Some(date(2029, 2, 28)));
+        assert_eq!(r.step(date(2031, 2, 28)), Some(date(2032, 2, 29)));
+    }
+}
@@ -0,0 +1,229 @@
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+
+use crate::model::{Status, TodoFile};
+use crate::sort;
+
+/// markdown / html の見出しのまとめ方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    #[default]
+    Status,
+    /// A todo with several tags appears under each of them
+    Tag,
+}
+
+/// 見出し1つ分（todos は渡された順のまま）
+struct Group<'a> {
+    name: String,
+    todos: Vec<&'a TodoFile>,
+}
+
+fn groups(todos: &[TodoFile], by: GroupBy) -> Vec<Group<'_>> {
+    match by {
+        GroupBy::Status => {
+            let mut statuses: Vec<&Status> = Vec::new();
+            for t in todos {
+                if !statuses.contains(&&t.fm.status) {
+                    statuses.push(&t.fm.status);
+                }
+            }
+            statuses.sort_by_key(|s| sort::status_rank(s));
+            statuses
+                .into_iter()
+                .map(|s| Group {
+                    name: s.as_str().to_string(),
+                    todos: todos.iter().filter(|t| &t.fm.status == s).collect(),
+                })
+                .collect()
+        }
+        GroupBy::Tag => {
+            let mut tags: Vec<&str> = todos.iter().flat_map(|t| t.fm.tags.iter().map(String::as_str)).collect();
+            tags.sort_by_key(|s| s.to_lowercase());
+            tags.dedup();
+            let mut out: Vec<Group> = tags
+                .into_iter()
+                .map(|tag| Group {
+                    name: tag.to_string(),
+                    todos: todos.iter().filter(|t| t.fm.tags.iter().any(|x| x == tag)).collect(),
+                })
+                .collect();
+            let untagged: Vec<&TodoFile> = todos.iter().filter(|t| t.fm.tags.is_empty()).collect();
+            if !untagged.is_empty() {
+                out.push(Group { name: "(no tag)".to_string(), todos: untagged });
+            }
+            out
+        }
+    }
+}
+
+/// 1行にまとめた frontmatter（status / due / importance / tags / repeat / id）
+fn meta(t: &TodoFile) -> Vec<String> {
+    let fm = &t.fm;
+    let mut v = vec![format!("status: {}", fm.status.as_str())];
+    if let Some(d) = &fm.due {
+        v.push(format!("due: {}", d));
+    }
+    v.push(format!("importance: {}", fm.importance));
+    if !fm.tags.is_empty() {
+        v.push(format!("tags: {}", fm.tags.join(", ")));
+    }
+    if let Some(r) = &fm.repeat {
+        v.push(format!("repeat: {}", r));
+    }
+    if let Some(d) = &fm.done_at {
+        let at = DateTime::parse_from_rfc3339(d)
+            .map(|d| d.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| d.clone());
+        v.push(format!("{}: {}", if fm.status == Status::Canceled { "canceled" } else { "done" }, at));
+    }
+    v.push(format!("id: {}", t.short_id()));
+    v
+}
+
+/// 本文の節（## メモ / ## サブタスク / ## ログ など）
+/// - テンプレートの空欄（"- [ ] " や "- :"）は落とし、中身の無い節は出さない
+/// - 最初の見出しより前の文章は heading 無しの節
+struct Section {
+    heading: Option<String>,
+    lines: Vec<String>,
+}
+
+fn sections(body: &str) -> Vec<Section> {
+    let mut out: Vec<Section> = vec![Section { heading: None, lines: Vec::new() }];
+    for line in body.lines() {
+        if let Some(h) = line.strip_prefix("## ") {
+            out.push(Section { heading: Some(h.trim().to_string()), lines: Vec::new() });
+            continue;
+        }
+        if matches!(line.trim(), "- [ ]" | "- :" | "-") {
+            continue;
+        }
+        if let Some(s) = out.last_mut() {
+            s.lines.push(line.trim_end().to_string());
+        }
+    }
+    for s in &mut out {
+        while s.lines.first().is_some_and(|l| l.is_empty()) {
+            s.lines.remove(0);
+        }
+        while s.lines.last().is_some_and(|l| l.is_empty()) {
+            s.lines.pop();
+        }
+    }
+    out.retain(|s| !s.lines.is_empty());
+    out
+}
+
+fn title(heading: &str, count: usize) -> String {
+    let now = Local::now().format("%Y-%m-%d %H:%M");
+    if heading.is_empty() {
+        format!("todo: {} todo(s), exported {}", count, now)
+    } else {
+        format!("todo: {} todo(s) [{}], exported {}", count, heading, now)
+    }
+}
+
+// ---- Markdown ----
+
+/// # todo … / ## グループ (n) / ### タイトル / #### 本文の節
+pub fn markdown(todos: &[TodoFile], by: GroupBy, heading: &str) -> String {
+    let mut out = format!("# {}\n", title(heading, todos.len()));
+    for g in groups(todos, by) {
+        out.push_str(&format!("\n## {} ({})\n", g.name, g.todos.len()));
+        for t in g.todos {
+            out.push_str(&format!("\n### {}\n\n", t.fm.title));
+            out.push_str(&format!("_{}_\n", meta(t).join(" · ")));
+            for s in sections(&t.body) {
+                out.push('\n');
+                if let Some(h) = &s.heading {
+                    out.push_str(&format!("#### {}\n\n", h));
+                }
+                for l in &s.lines {
+                    // 本文中の見出しも1段下げてレポートの構造を崩さない
+                    if l.starts_with('#') {
+                        out.push_str("###");
+                    }
+                    out.push_str(l);
+                    out.push('\n');
+                }
+            }
+        }
+    }
+    out
+}
+
+// ---- HTML ----
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:52rem;margin:2rem auto;padding:0 1rem;line-height:1.5}\
+h2{border-bottom:1px solid #ccc}\
+article{margin:0 0 1.5rem 1rem}\
+.meta{color:#666;font-size:.9em}\
+ul{padding-left:1.5rem}\
+li.task{list-style:none;margin-left:-1.2rem}";
+
+/// CSS 込みの1ファイル。本文は "- " の行を箇条書き、"- [ ]" / "- [x]" をチェックボックスにする
+pub fn html(todos: &[TodoFile], by: GroupBy, heading: &str) -> String {
+    let title = escape(&title(heading, todos.len()));
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n", title, STYLE));
+    out.push_str(&format!("<h1>{}</h1>\n", title));
+    for g in groups(todos, by) {
+        out.push_str(&format!("<section>\n<h2>{} ({})</h2>\n", escape(&g.name), g.todos.len()));
+        for t in g.todos {
+            out.push_str("<article>\n");
+            out.push_str(&format!("<h3>{}</h3>\n", escape(&t.fm.title)));
+            out.push_str(&format!("<p class=\"meta\">{}</p>\n", escape(&meta(t).join(" · "))));
+            for s in sections(&t.body) {
+                if let Some(h) = &s.heading {
+                    out.push_str(&format!("<h4>{}</h4>\n", escape(h)));
+                }
+                html_lines(&mut out, &s.lines);
+            }
+            out.push_str("</article>\n");
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn html_lines(out: &mut String, lines: &[String]) {
+    let mut in_list = false;
+    for l in lines {
+        let item = l.trim_start().strip_prefix("- ");
+        if item.is_some() != in_list {
+            out.push_str(if in_list { "</ul>\n" } else { "<ul>\n" });
+            in_list = !in_list;
+        }
+        match item {
+            Some(i) => {
+                let check = i
+                    .strip_prefix("[ ] ")
+                    .map(|rest| (false, rest))
+                    .or_else(|| i.strip_prefix("[x] ").or_else(|| i.strip_prefix("[X] ")).map(|rest| (true, rest)));
+                match check {
+                    Some((done, rest)) => out.push_str(&format!(
+                        "<li class=\"task\"><input type=\"checkbox\" disabled{}> {}</li>\n",
+                        if done { " checked" } else { "" },
+                        escape(rest)
+                    )),
+                    None => out.push_str(&format!("<li>{}</li>\n", escape(i))),
+                }
+            }
+            None if l.trim().is_empty() => {}
+            None => out.push_str(&format!("<p>{}</p>\n", escape(l))),
+        }
+    }
+    if in_list {
+        out.push_str("</ul>\n");
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset};
+use std::cmp::Ordering;
+
+use crate::model::{Status, TodoFile};
+use crate::store;
+
+/// --sort のキー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Due,
+    Importance,
+    Created,
+    Updated,
+    Status,
+    Title,
+    Tag,
+    Id,
+}
+
+/// キー1つ分（desc=true で降順）
+#[derive(Debug, Clone, Copy)]
+pub struct SortSpec {
+    pub key: SortKey,
+    pub desc: bool,
+}
+
+/// "due,-importance,title:asc" のようなカンマ区切り
+/// - 先頭の `-` か `:desc` で降順、`+` か `:asc`（省略時）で昇順
+pub fn parse(s: &str) -> Result<Vec<SortSpec>> {
+    let mut out = Vec::new();
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (name, mut desc) = match part.strip_prefix('-') {
+            Some(rest) => (rest, true),
+            None => (part.strip_prefix('+').unwrap_or(part), false),
+        };
+        let name = match name.split_once(':') {
+            Some((n, dir)) => {
+                desc = match dir.to_lowercase().as_str() {
+                    "asc" => false,
+                    "desc" => true,
+                    _ => return Err(anyhow!("invalid sort direction: {} (asc/desc)", dir)),
+                };
+                n
+            }
+            None => name,
+        };
+        let key = match name.to_lowercase().as_str() {
+            "due" => SortKey::Due,
+            "importance" | "imp" => SortKey::Importance,
+            "created" | "created_at" => SortKey::Created,
+            "updated" | "updated_at" => SortKey::Updated,
+            "status" => SortKey::Status,
+            "title" => SortKey::Title,
+            "tag" | "tags" => SortKey::Tag,
+            "id" => SortKey::Id,
+            _ => {
+                return Err(anyhow!(
+                    "unknown sort key: {} (due/importance/created/updated/status/title/tag/id)",
+                    name
+                ))
+            }
+        };
+        out.push(SortSpec { key, desc });
+    }
+    if out.is_empty() {
+        return Err(anyhow!("empty --sort"));
+    }
+    Ok(out)
+}
+
+/// 指定キーの順に安定ソートする（全キーで同じなら元の順序のまま）
+/// 値が無いもの（due 無し、タグ無し、読めない日付）は向きに関係なく末尾
+pub fn sort(todos: &mut [TodoFile], specs: &[SortSpec]) {
+    todos.sort_by(|a, b| {
+        for s in specs {
+            let ord = compare(a, b, s);
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        Ordering::Equal
+    });
+}
+
+fn compare(a: &TodoFile, b: &TodoFile, s: &SortSpec) -> Ordering {
+    let dir = |o: Ordering| if s.desc { o.reverse() } else { o };
+    match s.key {
+        SortKey::Due => missing_last(due(a), due(b), dir),
+        SortKey::Created => missing_last(rfc3339(&a.fm.created_at), rfc3339(&b.fm.created_at), dir),
+        SortKey::Updated => missing_last(rfc3339(&a.fm.updated_at), rfc3339(&b.fm.updated_at), dir),
+        SortKey::Tag => missing_last(first_tag(a), first_tag(b), dir),
+        SortKey::Importance => dir(a.fm.importance.cmp(&b.fm.importance)),
+        SortKey::Status => dir(status_rank(&a.fm.status).cmp(&status_rank(&b.fm.status))),
+        SortKey::Title => dir(a.fm.title.to_lowercase().cmp(&b.fm.title.to_lowercase())),
+        SortKey::Id => dir(a.fm.id.cmp(&b.fm.id)),
+    }
+}
+
+fn missing_last<T: Ord>(a: Option<T>, b: Option<T>, dir: impl Fn(Ordering) -> Ordering) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => dir(a.cmp(&b)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn due(t: &TodoFile) -> Option<DateTime<FixedOffset>> {
+    t.fm.due.as_deref().and_then(store::parse_due_dt)
+}
+
+fn rfc3339(s: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(s).ok()
+}
+
+/// 辞書順で最初のタグ（大文字小文字は区別しない）
+fn first_tag(t: &TodoFile) -> Option<String> {
+    t.fm.tags.iter().map(|x| x.to_lowercase()).min()
+}
+
+pub fn status_rank(s: &Status) -> u8 {
+    match s {
+        Status::Todo => 0,
+        Status::Doing => 1,
+        Status::Waiting => 2,
+        Status::Done => 3,
+        Status::Canceled => 4,
+    }
+}
@@ -0,0 +1,278 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate};
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::format::{self, Label};
+use crate::model::{Status, TodoFile};
+use crate::{due, filter, store};
+
+/// 集計の単位（週は月曜始まり、ISO 週番号で表示）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Period {
+    #[default]
+    Week,
+    Month,
+}
+
+/// 省略時の期間（単位いくつ分さかのぼるか）
+const DEFAULT_PERIODS: u32 = 12;
+
+#[derive(Default)]
+struct Counts {
+    created: usize,
+    done: usize,
+    canceled: usize,
+    /// created → done の日数（done のみ）
+    leads: Vec<f64>,
+    /// due があって期限後に done になった数 / due があった done の数
+    late: usize,
+    with_due: usize,
+}
+
+impl Counts {
+    fn add(&mut self, a: &Activity) {
+        if a.created {
+            self.created += 1;
+        }
+        match a.closed {
+            Some(Status::Done) => {
+                self.done += 1;
+                self.leads.extend(a.lead);
+                if let Some(late) = a.late {
+                    self.with_due += 1;
+                    if late {
+                        self.late += 1;
+                    }
+                }
+            }
+            Some(Status::Canceled) => self.canceled += 1,
+            _ => {}
+        }
+    }
+}
+
+/// todo 1件が期間内にしたこと
+struct Activity {
+    created: bool,
+    closed: Option<Status>,
+    lead: Option<f64>,
+    late: Option<bool>,
+}
+
+/// `todo stats`：active と done/canceled（archive 済み含む）から
+/// 期間ごとの作成・完了・取り消し数、リードタイム、期限超過率、タグ別の内訳を出す
+pub fn run(cfg: &Config, since: Option<&str>, until: Option<&str>, tag: Option<&str>, by: Period) -> Result<()> {
+    let until = match until {
+        Some(s) => due::parse_past(s)?,
+        None => Local::now().date_naive(),
+    };
+    let since = match since {
+        Some(s) => due::parse_past(s)?,
+        None => step(bucket_start(until, by), by, -(DEFAULT_PERIODS as i32 - 1)),
+    };
+    if since > until {
+        return Err(anyhow!("--since {} is after --until {}", since, until));
+    }
+
+    let mut todos = store::load_all(cfg)?;
+    if let Some(tag) = tag {
+        filter::retain_tag(&mut todos, tag);
+    }
+
+    // 期間の箱（範囲外の日付のものは数えない）
+    let mut buckets: BTreeMap<NaiveDate, Counts> = BTreeMap::new();
+    let mut b = bucket_start(since, by);
+    while b <= until {
+        buckets.insert(b, Counts::default());
+        b = step(b, by, 1);
+    }
+    let mut total = Counts::default();
+    let mut by_tag: BTreeMap<String, Counts> = BTreeMap::new();
+
+    let in_range = |d: NaiveDate| d >= since && d <= until;
+    for t in &todos {
+        let created = local_date(&t.fm.created_at).filter(|d| in_range(*d));
+        let closed_on = t.fm.done_at.as_deref().and_then(local_date).filter(|d| in_range(*d));
+        let closed = closed_on.and(match t.fm.status {
+            Status::Done | Status::Canceled => Some(t.fm.status.clone()),
+            _ => None,
+        });
+
+        if let Some(d) = created {
+            buckets.entry(bucket_start(d, by)).or_default().add(&Activity { created: true, closed: None, lead: None, late: None });
+        }
+        if let (Some(d), Some(st)) = (closed_on, closed.clone()) {
+            let a = Activity { created: false, closed: Some(st), lead: lead_days(t), late: late(t) };
+            buckets.entry(bucket_start(d, by)).or_default().add(&a);
+        }
+        if created.is_none() && closed.is_none() {
+            continue;
+        }
+
+        let a = Activity { created: created.is_some(), closed: closed.clone(), lead: lead_days(t), late: late(t) };
+        total.add(&a);
+        if t.fm.tags.is_empty() {
+            by_tag.entry("(no tag)".to_string()).or_default().add(&a);
+        }
+        for tag in &t.fm.tags {
+            by_tag.entry(tag.clone()).or_default().add(&a);
+        }
+    }
+
+    let title = match tag {
+        Some(tag) => format!("{} .. {}  (by {}, tag: {})", since, until, period_name(by), tag),
+        None => format!("{} .. {}  (by {})", since, until, period_name(by)),
+    };
+    println!("{}", title);
+    println!();
+
+    // 期間ごと
+    let max_done = buckets.values().map(|c| c.done).max().unwrap_or(0);
+    println!("{}  {:>7}  {:>4}  {:>8}", format::pad(period_name(by), 8), "created", "done", "canceled");
+    for (start, c) in &buckets {
+        let bar = format::bar(c.done, max_done);
+        let line = format!(
+            "{}  {:>7}  {:>4}  {:>8}  {}",
+            format::pad(&period_label(*start, by), 8),
+            c.created,
+            c.done,
+            c.canceled,
+            bar
+        );
+        println!("{}", line.trim_end());
+    }
+    println!("{}  {:>7}  {:>4}  {:>8}", format::pad("total", 8), total.created, total.done, total.canceled);
+    println!();
+    println!("created   {}", sparkline(buckets.values().map(|c| c.created)));
+    println!("done      {}", sparkline(buckets.values().map(|c| c.done)));
+    println!();
+
+    // リードタイム・期限
+    if total.leads.is_empty() {
+        println!("lead time (created → done): -");
+    } else {
+        let mut leads = total.leads.clone();
+        leads.sort_by(|a, b| a.total_cmp(b));
+        let avg = leads.iter().sum::<f64>() / leads.len() as f64;
+        let median = if leads.len() % 2 == 1 {
+            leads[leads.len() / 2]
+        } else {
+            (leads[leads.len() / 2 - 1] + leads[leads.len() / 2]) / 2.0
+        };
+        println!(
+            "lead time (created → done): avg {}, median {}, max {} ({} done)",
+            days(avg),
+            days(median),
+            days(*leads.last().unwrap_or(&0.0)),
+            leads.len()
+        );
+    }
+    if total.with_due == 0 {
+        println!("overdue rate: - (no done todo had a due)");
+    } else {
+        println!(
+            "overdue rate: {} of {} done with a due finished late ({}%)",
+            total.late,
+            total.with_due,
+            total.late * 100 / total.with_due
+        );
+    }
+    let active: Vec<&TodoFile> = todos.iter().filter(|t| t.fm.status.is_active()).collect();
+    let count = |s: Status| active.iter().filter(|t| t.fm.status == s).count();
+    let overdue_now = active.iter().filter(|t| format::label_for(t, cfg.soon_days) == Label::Overdue).count();
+    println!(
+        "active now: {} (todo {}, doing {}, waiting {}), {} overdue",
+        active.len(),
+        count(Status::Todo),
+        count(Status::Doing),
+        count(Status::Waiting),
+        overdue_now
+    );
+
+    // タグ別（done の多い順）
+    if !by_tag.is_empty() {
+        let mut rows: Vec<(&String, &Counts)> = by_tag.iter().collect();
+        rows.sort_by(|a, b| b.1.done.cmp(&a.1.done).then(b.1.created.cmp(&a.1.created)));
+        let w = rows.iter().map(|(name, _)| format::width(name)).max().unwrap_or(0).max(3);
+        println!();
+        println!("{}  {:>7}  {:>4}  {:>8}  {:>8}  {:>4}", format::pad("tag", w), "created", "done", "canceled", "avg lead", "late");
+        for (name, c) in rows {
+            let avg = if c.leads.is_empty() { "-".to_string() } else { days(c.leads.iter().sum::<f64>() / c.leads.len() as f64) };
+            println!(
+                "{}  {:>7}  {:>4}  {:>8}  {:>8}  {:>4}",
+                format::pad(name, w),
+                c.created,
+                c.done,
+                c.canceled,
+                avg,
+                c.late
+            );
+        }
+    }
+    Ok(())
+}
+
+fn period_name(by: Period) -> &'static str {
+    match by {
+        Period::Week => "week",
+        Period::Month => "month",
+    }
+}
+
+fn bucket_start(d: NaiveDate, by: Period) -> NaiveDate {
+    match by {
+        Period::Week => d - Duration::days(d.weekday().num_days_from_monday() as i64),
+        Period::Month => d.with_day(1).unwrap_or(d),
+    }
+}
+
+fn step(d: NaiveDate, by: Period, n: i32) -> NaiveDate {
+    match by {
+        Period::Week => d + Duration::weeks(n as i64),
+        Period::Month if n >= 0 => d.checked_add_months(Months::new(n as u32)).unwrap_or(d),
+        Period::Month => d.checked_sub_months(Months::new(n.unsigned_abs())).unwrap_or(d),
+    }
+}
+
+fn period_label(start: NaiveDate, by: Period) -> String {
+    match by {
+        Period::Week => start.format("%G-W%V").to_string(),
+        Period::Month => start.format("%Y-%m").to_string(),
+    }
+}
+
+fn local_date(rfc3339: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(rfc3339).ok().map(|d| d.with_timezone(&Local).date_naive())
+}
+
+fn lead_days(t: &TodoFile) -> Option<f64> {
+    let created = DateTime::parse_from_rfc3339(&t.fm.created_at).ok()?;
+    let done = DateTime::parse_from_rfc3339(t.fm.done_at.as_deref()?).ok()?;
+    Some(((done - created).num_seconds().max(0)) as f64 / 86400.0)
+}
+
+/// due があれば期限後に閉じたか
+fn late(t: &TodoFile) -> Option<bool> {
+    let due = store::parse_due_dt(t.fm.due.as_deref()?)?;
+    let done = DateTime::parse_from_rfc3339(t.fm.done_at.as_deref()?).ok()?;
+    Some(done > due)
+}
+
+/// 1日未満は時間で
+fn days(d: f64) -> String {
+    if d < 1.0 {
+        format!("{:.1}h", d * 24.0)
+    } else {
+        format!("{:.1}d", d)
+    }
+}
+
+/// 0 は一番低い段、それ以外は最大値に対する割合で ▂..█
+fn sparkline(values: impl Iterator<Item = usize>) -> String {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let values: Vec<usize> = values.collect();
+    let max = values.iter().copied().max().unwrap_or(0);
+    values.iter().map(|&n| LEVELS[format::scale(n, max, LEVELS.len() - 1)]).collect()
+}
@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use std::io::Write;
+
+use crate::clock;
+use crate::config::Config;
+use crate::format::{self, Label};
+use crate::model::{Status, TodoFile};
+use crate::store;
+
+/// --user-var の名前を省略したとき
+pub const DEFAULT_USER_VAR: &str = "todo_status";
+
+/// テンプレートで使える置き換え
+const KEYS: [&str; 7] = ["overdue", "today", "soon", "active", "doing", "doing_count", "clock"];
+
+/// ステータスバーに出す分だけ（active/ を index 経由で読むだけなので数秒おきに呼んでも軽い）
+struct Summary {
+    overdue: usize,
+    today: usize,
+    soon: usize,
+    active: usize,
+    doing_count: usize,
+    /// 計測中の todo、なければ一番最近更新した doing
+    doing: Option<String>,
+    /// 計測中なら今の区間の経過時間
+    clock: Option<String>,
+}
+
+fn summarize(cfg: &Config, title_width: usize) -> Result<Summary> {
+    let mut list = store::load_active(cfg)?;
+    list.retain(|t| t.fm.status.is_active());
+    let count = |l: Label| list.iter().filter(|t| format::label_for(t, cfg.soon_days) == l).count();
+
+    let running = list.iter().find_map(|t| clock::running(t).map(|s| (t, s)));
+    let doing: Option<&TodoFile> = running.map(|(t, _)| t).or_else(|| {
+        list.iter().filter(|t| t.fm.status == Status::Doing).max_by(|a, b| a.fm.updated_at.cmp(&b.fm.updated_at))
+    });
+    let clock = running.map(|(_, start)| clock::fmt_duration((Local::now().fixed_offset() - start).num_seconds()));
+
+    Ok(Summary {
+        overdue: count(Label::Overdue),
+        today: count(Label::Today),
+        soon: count(Label::Soon),
+        active: list.len(),
+        doing_count: list.iter().filter(|t| t.fm.status == Status::Doing).count(),
+        doing: doing.map(|t| format::truncate(&t.fm.title, title_width)),
+        clock,
+    })
+}
+
+/// 既定の形：`OVERDUE 2  TODAY 1  SOON 3  ▶ タイトル 25m`（0 件・doing 無しの部分は出さない）
+fn default_line(s: &Summary) -> String {
+    let mut parts = Vec::new();
+    for (label, n) in [(Label::Overdue, s.overdue), (Label::Today, s.today), (Label::Soon, s.soon)] {
+        if n > 0 {
+            parts.push(format!("{} {}", format::label_str(label), n));
+        }
+    }
+    if let Some(title) = &s.doing {
+        match &s.clock {
+            Some(c) => parts.push(format!("▶ {} {}", title, c)),
+            None => parts.push(format!("▶ {}", title)),
+        }
+    }
+    parts.join("  ")
+}
+
+/// `{overdue}` などを置き換える。`{{` / `}}` は波かっこそのもの
+fn render(template: &str, s: &Summary) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if let Some(r) = tail.strip_prefix("{{") {
+            out.push('{');
+            rest = r;
+            continue;
+        }
+        if let Some(r) = tail.strip_prefix("}}") {
+            out.push('}');
+            rest = r;
+            continue;
+        }
+        let Some(end) = tail.find('}').filter(|_| tail.starts_with('{')) else {
+            return Err(anyhow!("unbalanced brace in --format: {}", template));
+        };
+        let key = &tail[1..end];
+        let value = match key {
+            "overdue" => s.overdue.to_string(),
+            "today" => s.today.to_string(),
+            "soon" => s.soon.to_string(),
+            "active" => s.active.to_string(),
+            "doing_count" => s.doing_count.to_string(),
+            "doing" => s.doing.clone().unwrap_or_default(),
+            "clock" => s.clock.clone().unwrap_or_default(),
+            _ => return Err(anyhow!("unknown placeholder {{{}}} (use {})", key, KEYS.join("/"))),
+        };
+        out.push_str(&value);
+        rest = &tail[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// `todo status-line`
+/// - template が無ければ default_line
+/// - user_var があれば本文を出さず、OSC 1337 SetUserVar（値は base64）だけを書く
+///   WezTerm の Lua からは `pane:get_user_vars()[name]` で読める
+pub fn run(cfg: &Config, template: Option<&str>, user_var: Option<&str>, title_width: usize) -> Result<()> {
+    let s = summarize(cfg, title_width)?;
+    let line = match template {
+        Some(t) => render(t, &s)?,
+        None => default_line(&s),
+    };
+    let mut out = std::io::stdout();
+    match user_var {
+        Some(name) => {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                return Err(anyhow!("invalid user var name: {:?} (use letters, digits, _ and -)", name));
+            }
+            write!(out, "\x1b]1337;SetUserVar={}={}\x07", name, base64(line.as_bytes()))?;
+        }
+        None => writeln!(out, "{}", line)?,
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// 標準の base64（パディングあり）。SetUserVar のためだけなので依存は増やさない
+fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(TABLE[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> Summary {
+        Summary {
+            overdue: 2,
+            today: 1,
+            soon: 0,
+            active: 9,
+            doing_count: 1,
+            doing: Some("週報".to_string()),
+            clock: Some("25m".to_string()),
+        }
+    }
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(&[0xff, 0xfe, 0xfd]), "//79");
+        // 多バイト文字もそのままバイト列として
+        assert_eq!(base64("▶ 週報".as_bytes()), "4pa2IOmAseWgsQ==");
+    }
+
+    #[test]
+    fn render_placeholders_and_escapes() {
+        let s = summary();
+        assert_eq!(render("{overdue}/{today}/{soon} of {active}", &s).unwrap(), "2/1/0 of 9");
+        assert_eq!(render("{doing} {clock} ({doing_count})", &s).unwrap(), "週報 25m (1)");
+        assert_eq!(render("{{overdue}} = {overdue}", &s).unwrap(), "{overdue} = 2");
+        assert_eq!(render("}}{{", &s).unwrap(), "}{");
+        assert_eq!(render("no keys", &s).unwrap(), "no keys");
+
+        let idle = Summary { doing: None, clock: None, ..summary() };
+        assert_eq!(render("[{doing}{clock}]", &idle).unwrap(), "[]");
+    }
+
+    #[test]
+    fn render_errors() {
+        let s = summary();
+        let e = render("{nope}", &s).unwrap_err().to_string();
+        assert!(e.contains("unknown placeholder {nope}"), "{}", e);
+        assert!(render("{overdue", &s).unwrap_err().to_string().contains("unbalanced"));
+        assert!(render("overdue}", &s).unwrap_err().to_string().contains("unbalanced"));
+        assert!(render("{", &s).is_err());
+    }
+
+    #[test]
+    fn default_line_skips_empty_parts() {
+        let s = summary();
+        assert_eq!(default_line(&s), format!("{} 2  {} 1  ▶ 週報 25m", format::label_str(Label::Overdue), format::label_str(Label::Today)));
+        let quiet = Summary { overdue: 0, today: 0, soon: 0, doing: None, clock: None, ..summary() };
+        assert_eq!(default_line(&quiet), "");
+    }
+}
@@ -2,11 +2,20 @@ use anyhow::{anyhow, Result};
 use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, TimeZone};
 use walkdir::WalkDir;
 
-use crate::config::Config;
+use crate::activity;
+use crate::config::{Config, PickerMode, View};
 use crate::frontmatter::{parse_todo_file, render_todo_file};
+use crate::filter;
+use crate::index::{Area, Index};
 use crate::model::{Status, TodoFile};
+use crate::journal::Tx;
+use crate::picker::{self, Candidate};
+use crate::validate;
 
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions, TryLockError};
 use std::io::Write;
+use std::path::Path;
 use std::process::{Command, Stdio};
 
 pub fn ensure_dirs(cfg: &Config) -> Result<()> {
@@ -17,57 +26,119 @@ pub fn ensure_dirs(cfg: &Config) -> Result<()> {
     Ok(())
 }
 
+/// active/ だけ（archive 側の index は開かない）
 pub fn load_active(cfg: &Config) -> Result<Vec<TodoFile>> {
-    load_from_dir_recursive(&cfg.active_dir())
+    let mut idx = Index::load(cfg, Area::Active);
+    let out = load_from_dir_recursive(&mut idx, &cfg.active_dir())?;
+    idx.save().ok();
+    Ok(out)
 }
 
-/// active + archived done/canceled (reopen対象)
-pub fn load_closed(cfg: &Config) -> Result<Vec<TodoFile>> {
+/// done/ と canceled/（archive 側の index から）
+fn load_archive(cfg: &Config) -> Result<Vec<TodoFile>> {
+    let mut idx = Index::load(cfg, Area::Archive);
     let mut out = Vec::new();
+    for dir in [cfg.done_dir(), cfg.canceled_dir()] {
+        out.extend(load_from_dir_recursive(&mut idx, &dir)?);
+    }
+    idx.save().ok();
+    Ok(out)
+}
 
-    // active配下に done/canceled が残っている場合にも対応
-    out.extend(load_from_dir_recursive(&cfg.active_dir())?);
-
-    // archive済み
-    out.extend(load_from_dir_recursive(&cfg.done_dir())?);
-    out.extend(load_from_dir_recursive(&cfg.canceled_dir())?);
+/// done/ と canceled/ のうち since の月以降の YYYY/MM だけ（index を使わずに直接読む。壊れたファイルは飛ばす）
+pub fn load_archive_since(cfg: &Config, since: NaiveDate) -> Result<Vec<TodoFile>> {
+    let mut out = Vec::new();
+    for base in [cfg.done_dir(), cfg.canceled_dir()] {
+        for entry in WalkDir::new(&base).min_depth(2).max_depth(2).into_iter().filter_map(|e| e.ok()) {
+            let rel = entry.path().strip_prefix(&base).unwrap_or(entry.path());
+            let ym: Vec<u32> = rel.iter().filter_map(|p| p.to_str()?.parse().ok()).collect();
+            if !entry.file_type().is_dir() || ym.len() != 2 || (ym[0] as i32, ym[1]) < (since.year(), since.month()) {
+                continue;
+            }
+            for f in WalkDir::new(entry.path()).into_iter().filter_map(|e| e.ok()) {
+                if f.path().extension().and_then(|s| s.to_str()) != Some("md") { continue; }
+                let Ok(text) = std::fs::read_to_string(f.path()) else { continue; };
+                if let Ok(todo) = parse_todo_file(f.path().to_path_buf(), &text) {
+                    out.push(todo);
+                }
+            }
+        }
+    }
+    Ok(out)
+}
 
+/// active + archived done/canceled (reopen対象)
+pub fn load_closed(cfg: &Config) -> Result<Vec<TodoFile>> {
+    // active配下に done/canceled が残っている場合にも対応
+    let mut out = load_active(cfg)?;
+    out.extend(load_archive(cfg)?);
     out.retain(|t| matches!(t.fm.status, Status::Done | Status::Canceled));
     Ok(out)
 }
 
-fn load_from_dir_recursive(dir: &std::path::Path) -> Result<Vec<TodoFile>> {
+/// active + done + canceled 全件（status問わず）
+pub fn load_all(cfg: &Config) -> Result<Vec<TodoFile>> {
+    let mut out = load_active(cfg)?;
+    out.extend(load_archive(cfg)?);
+    Ok(out)
+}
+
+/// index を捨てて active/done/canceled を全て読み直す（件数を返す）
+pub fn rebuild_index(cfg: &Config) -> Result<usize> {
+    let mut n = 0;
+    for (area, dirs) in [(Area::Active, vec![cfg.active_dir()]), (Area::Archive, vec![cfg.done_dir(), cfg.canceled_dir()])] {
+        let mut idx = Index::load(cfg, area);
+        idx.clear();
+        for dir in dirs {
+            load_from_dir_recursive(&mut idx, &dir)?;
+        }
+        idx.save()?;
+        n += idx.len();
+    }
+    Ok(n)
+}
+
+/// index にキャッシュがあれば使い、無い/古いものだけ読み直す
+fn load_from_dir_recursive(idx: &mut Index, dir: &std::path::Path) -> Result<Vec<TodoFile>> {
     let mut out = Vec::new();
     if !dir.exists() {
         return Ok(out);
     }
+    let mut seen = HashSet::new();
     for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
         if !entry.file_type().is_file() { continue; }
         if entry.path().extension().and_then(|s| s.to_str()) != Some("md") { continue; }
-        let text = std::fs::read_to_string(entry.path())?;
-        let todo = parse_todo_file(entry.path().to_path_buf(), &text)?;
+        let todo = idx.get_or_parse(entry.path())?;
+        seen.insert(entry.path().to_path_buf());
         out.push(todo);
     }
+    idx.retain_seen(dir, &seen);
     Ok(out)
 }
 
 /// Resolve id or prefix within ACTIVE directory.
 pub fn resolve_one(cfg: &Config, id_or_prefix: &str) -> Result<TodoFile> {
     let list = load_active(cfg)?;
-    resolve_from_list(&list, id_or_prefix)
+    resolve_from_list(cfg, &list, id_or_prefix)
 }
 
 /// Resolve id or prefix within CLOSED set (active+archive done/canceled).
 pub fn resolve_one_closed(cfg: &Config, id_or_prefix: &str) -> Result<TodoFile> {
     let list = load_closed(cfg)?;
-    resolve_from_list(&list, id_or_prefix)
+    resolve_from_list(cfg, &list, id_or_prefix)
 }
 
-fn resolve_from_list(list: &[TodoFile], id_or_prefix: &str) -> Result<TodoFile> {
+/// Resolve id or prefix within ALL todos (active + archive).
+pub fn resolve_one_any(cfg: &Config, id_or_prefix: &str) -> Result<TodoFile> {
+    let list = load_all(cfg)?;
+    resolve_from_list(cfg, &list, id_or_prefix)
+}
+
+fn resolve_from_list(cfg: &Config, list: &[TodoFile], id_or_prefix: &str) -> Result<TodoFile> {
     let mut matches: Vec<TodoFile> = list
         .iter()
-        .cloned()
         .filter(|t| t.fm.id == id_or_prefix || t.fm.id.starts_with(id_or_prefix))
+        .cloned()
         .collect();
 
     if matches.is_empty() {
@@ -77,11 +148,11 @@ fn resolve_from_list(list: &[TodoFile], id_or_prefix: &str) -> Result<TodoFile>
         return Ok(matches.remove(0));
     }
 
-    if let Some(selected) = fzf_select_todos(&matches) {
+    if let Some(selected) = select_todos(cfg, &matches) {
         return Ok(selected);
     }
 
-    let mut msg = String::from("multiple matches (use longer prefix or install fzf):\n");
+    let mut msg = String::from("multiple matches (use longer prefix):\n");
     for t in matches.iter().take(30) {
         msg.push_str(&format!(
             "  {}  [{}]  {}  ({})\n",
@@ -94,12 +165,73 @@ fn resolve_from_list(list: &[TodoFile], id_or_prefix: &str) -> Result<TodoFile>
     Err(anyhow!(msg))
 }
 
-pub fn save(todo: &TodoFile) -> Result<()> {
+/// 書き込み前に frontmatter を検査する（今回の変更で不正になったなら書かずにエラー）
+pub fn save(tx: &mut Tx, todo: &TodoFile) -> Result<()> {
+    let before = std::fs::read_to_string(&todo.path).ok().and_then(|t| parse_todo_file(todo.path.clone(), &t).ok());
+    validate::ensure_valid(todo, before.as_ref().map(|b| &b.fm))?;
+    write_todo(tx, todo)
+}
+
+/// 検査なしで書き込む（doctor が直せた分だけ書き戻す用）
+pub fn write_todo(tx: &mut Tx, todo: &TodoFile) -> Result<()> {
     let text = render_todo_file(&todo.fm, &todo.body)?;
-    std::fs::write(&todo.path, text)?;
+    tx.note_write(&todo.path);
+    write_atomic(&todo.path, text.as_bytes())
+}
+
+/// 同じディレクトリの一時ファイルに書いて fsync → rename
+/// 途中で落ちても元のファイルか新しいファイルのどちらかが残る
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let dir = path.parent().ok_or_else(|| anyhow!("bad path: {}", path.display()))?;
+    let name = path.file_name().and_then(|s| s.to_str()).ok_or_else(|| anyhow!("bad filename: {}", path.display()))?;
+    let tmp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+
+    let res = (|| -> Result<()> {
+        let mut f = File::create(&tmp)?;
+        f.write_all(bytes)?;
+        f.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    })();
+    if res.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    res?;
+
+    // rename 自体を永続化（ディレクトリの fsync。できない環境では無視）
+    if let Ok(d) = File::open(dir) {
+        let _ = d.sync_all();
+    }
     Ok(())
 }
 
+/// root_dir 単位の advisory lock（drop で解放）
+pub struct RootLock {
+    _file: File,
+}
+
+/// 更新系の処理の前に取る。別のペインの todo が持っていれば解放を待つ
+pub fn lock_root(cfg: &Config) -> Result<RootLock> {
+    let path = cfg.lock_path();
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(&path)?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            eprintln!("waiting for another todo process ({})...", path.display());
+            file.lock()?;
+        }
+        Err(TryLockError::Error(e)) => return Err(anyhow!("failed to lock {}: {}", path.display(), e)),
+    }
+    Ok(RootLock { _file: file })
+}
+
+/// lock を取った後に読み直す（選択してから lock までの間に他で変更・移動されていないか）
+pub fn reload(todo: &TodoFile) -> Result<TodoFile> {
+    let text = std::fs::read_to_string(&todo.path)
+        .map_err(|_| anyhow!("{} was moved or removed by another process; try again", todo.path.display()))?;
+    parse_todo_file(todo.path.clone(), &text)
+}
+
 pub fn now_jst_rfc3339() -> String {
     Local::now().to_rfc3339()
 }
@@ -142,7 +274,7 @@ pub fn month_dir(base: &std::path::Path, done_at: &str) -> Result<std::path::Pat
         .join(format!("{:02}", dt.month())))
 }
 
-pub fn move_to_archive(cfg: &Config, todo: &TodoFile) -> Result<std::path::PathBuf> {
+pub fn move_to_archive(cfg: &Config, tx: &mut Tx, todo: &TodoFile) -> Result<std::path::PathBuf> {
     let done_at = todo
         .fm
         .done_at
@@ -161,6 +293,7 @@ pub fn move_to_archive(cfg: &Config, todo: &TodoFile) -> Result<std::path::PathB
     let file_name = todo.path.file_name().ok_or_else(|| anyhow!("bad filename"))?;
     let dest = dest_dir.join(file_name);
 
+    tx.note_rename(&todo.path, &dest);
     std::fs::rename(&todo.path, &dest)?;
     Ok(dest)
 }
@@ -174,33 +307,112 @@ fn has_cmd(cmd: &str) -> bool {
         .is_ok()
 }
 
-/// fzf-select from ACTIVE todos (todo/doing/waiting)
-pub fn select_active_one_fzf(cfg: &Config) -> Result<Option<TodoFile>> {
+/// picker 設定に従って fzf / 内蔵ピッカーを使い分ける
+fn use_fzf(cfg: &Config) -> bool {
+    match cfg.picker {
+        PickerMode::Fzf => true,
+        PickerMode::Builtin => false,
+        PickerMode::Auto => has_cmd("fzf"),
+    }
+}
+
+/// ピッカーで選ばれたもの（候補一覧での todo の位置か、一覧を絞り込む view）
+enum Picked {
+    Todo(usize),
+    View(String),
+}
+
+/// Select from ACTIVE todos (todo/doing/waiting) via fzf or the built-in picker
+/// config に views があれば先頭に "@name" の候補を並べ、選ぶとその view で絞った一覧で選び直す
+pub fn select_active_one(cfg: &Config) -> Result<Option<TodoFile>> {
     let mut list = load_active(cfg)?;
     list.retain(|t| t.fm.status.is_active());
     if list.is_empty() {
         return Ok(None);
     }
-    Ok(fzf_select_todos(&list))
+
+    let views: Vec<(String, String)> = cfg.views.iter().map(|(name, v)| (name.clone(), view_display(name, v))).collect();
+    let name = match pick_todo_or_view(cfg, "todo> ", &list, &views) {
+        Some(Picked::Todo(i)) => return Ok(Some(list[i].clone())),
+        Some(Picked::View(name)) => name,
+        None => return Ok(None),
+    };
+
+    let scoped = filter::apply(cfg, list, &cfg.view(&name)?.filter)?;
+    if scoped.is_empty() {
+        println!("no todos in view {}", name);
+        return Ok(None);
+    }
+    match pick_todo_or_view(cfg, &format!("todo @{}> ", name), &scoped, &[]) {
+        Some(Picked::Todo(i)) => Ok(Some(scoped[i].clone())),
+        _ => Ok(None),
+    }
 }
 
-/// fzf-select from CLOSED todos (done/canceled) including archived
-pub fn select_closed_one_fzf(cfg: &Config) -> Result<Option<TodoFile>> {
+/// Select from CLOSED todos (done/canceled) including archived
+pub fn select_closed_one(cfg: &Config) -> Result<Option<TodoFile>> {
     let list = load_closed(cfg)?;
     if list.is_empty() {
         return Ok(None);
     }
-    Ok(fzf_select_todos(&list))
+    Ok(select_todos(cfg, &list))
+}
+
+fn select_todos(cfg: &Config, matches: &[TodoFile]) -> Option<TodoFile> {
+    match pick_todo_or_view(cfg, "todo> ", matches, &[]) {
+        Some(Picked::Todo(i)) => Some(matches[i].clone()),
+        _ => None,
+    }
+}
+
+/// views: (名前, 表示文字列)。todo より前に並べる
+fn pick_todo_or_view(cfg: &Config, prompt: &str, matches: &[TodoFile], views: &[(String, String)]) -> Option<Picked> {
+    if use_fzf(cfg) {
+        return fzf_select_todos(prompt, matches, views);
+    }
+    let mut items: Vec<Candidate> = views
+        .iter()
+        .map(|(_, display)| Candidate { display: display.clone(), path: None })
+        .collect();
+    items.extend(matches.iter().map(|t| Candidate { display: todo_display(t), path: Some(t.path.clone()) }));
+    let i = picker::pick(prompt, &items, &cfg.editor)?;
+    Some(match i.checked_sub(views.len()) {
+        Some(j) => Picked::Todo(j),
+        None => Picked::View(views[i].0.clone()),
+    })
+}
+
+fn view_display(name: &str, view: &View) -> String {
+    let desc = view.description.as_deref().map(|d| format!("{}  ", d)).unwrap_or_default();
+    format!("@{}  {}[{}]", name, desc, filter::describe(&view.filter))
+}
+
+/// 選択候補の表示文字列（fzf / 内蔵ピッカー共通）
+fn todo_display(t: &TodoFile) -> String {
+    let due = t.fm.due.clone().unwrap_or_else(|| "----".to_string());
+    let tags = if t.fm.tags.is_empty() {
+        "".to_string()
+    } else {
+        format!(" ({})", t.fm.tags.join(","))
+    };
+    format!(
+        "[{}] {} {}{}  {}",
+        t.fm.importance,
+        due,
+        t.fm.title,
+        tags,
+        t.path.display()
+    )
 }
 
 /// Candidate format (TAB-delimited):
-///   1: id (hidden)
+///   1: id (hidden; "@view:<name>" for views)
 ///   2: display text (shown)
-///   3: path (for preview)
+///   3: path (for preview; empty for views)
 /// fzf:
 /// - preview: bat/batcat or sed
 /// - Ctrl-O: open in $EDITOR (or nvim) without leaving fzf
-fn fzf_select_todos(matches: &[TodoFile]) -> Option<TodoFile> {
+fn fzf_select_todos(prompt: &str, matches: &[TodoFile], views: &[(String, String)]) -> Option<Picked> {
     if !has_cmd("fzf") {
         return None;
     }
@@ -209,21 +421,11 @@ fn fzf_select_todos(matches: &[TodoFile]) -> Option<TodoFile> {
     let bat_cmd = if has_cmd("bat") { "bat" } else { "batcat" };
 
     let mut lines = String::new();
+    for (name, display) in views {
+        lines.push_str(&format!("@view:{}\t{}\t\n", name, display));
+    }
     for t in matches {
-        let due = t.fm.due.clone().unwrap_or_else(|| "----".to_string());
-        let tags = if t.fm.tags.is_empty() {
-            "".to_string()
-        } else {
-            format!(" ({})", t.fm.tags.join(","))
-        };
-        let display = format!(
-            "[{}] {} {}{}  {}",
-            t.fm.importance,
-            due,
-            t.fm.title,
-            tags,
-            t.path.display()
-        );
+        let display = todo_display(t);
         lines.push_str(&format!(
             "{}\t{}\t{}\n",
             t.fm.id,
@@ -232,19 +434,21 @@ fn fzf_select_todos(matches: &[TodoFile]) -> Option<TodoFile> {
         ));
     }
 
+    // view の行はパスが空なので preview / Ctrl-O しない
     let preview = if use_bat {
-        format!("{bat_cmd} --style=numbers --color=always --line-range :200 {{3}}")
+        format!("[ -n {{3}} ] && {bat_cmd} --style=numbers --color=always --line-range :200 {{3}}")
     } else {
-        "sh -lc 'sed -n \"1,200p\" \"{3}\"'".to_string()
+        "[ -n {3} ] && sh -lc 'sed -n \"1,200p\" \"{3}\"'".to_string()
     };
 
-    let bind_ctrl_o = "ctrl-o:execute-silent(sh -lc '${EDITOR:-nvim} \"{3}\"')";
+    let bind_ctrl_o = "ctrl-o:execute-silent([ -n {3} ] && sh -lc '${EDITOR:-nvim} \"{3}\"')";
+    let prompt = format!("--prompt={}", prompt);
 
     let mut child = Command::new("fzf")
         .args([
             "--delimiter=\t",
             "--with-nth=2",
-            "--prompt=todo> ",
+            &prompt,
             "--height=40%",
             "--reverse",
             "--preview-window=right:60%:wrap",
@@ -276,10 +480,13 @@ fn fzf_select_todos(matches: &[TodoFile]) -> Option<TodoFile> {
     }
 
     let id = selected_line.split('\t').next()?.trim();
-    matches.iter().find(|t| t.fm.id == id).cloned()
+    if let Some(name) = id.strip_prefix("@view:") {
+        return Some(Picked::View(name.to_string()));
+    }
+    matches.iter().position(|t| t.fm.id == id).map(Picked::Todo)
 }
 
-fn move_file_avoiding_collision(src: &std::path::Path, dest_dir: &std::path::Path) -> Result<std::path::PathBuf> {
+fn move_file_avoiding_collision(tx: &mut Tx, src: &std::path::Path, dest_dir: &std::path::Path) -> Result<std::path::PathBuf> {
     std::fs::create_dir_all(dest_dir)?;
 
     let file_name = src
@@ -301,53 +508,82 @@ fn move_file_avoiding_collision(src: &std::path::Path, dest_dir: &std::path::Pat
         }
     }
 
+    tx.note_rename(src, &dest);
     std::fs::rename(src, &dest)?;
     Ok(dest)
 }
 
-fn quarantine_broken(root: &std::path::Path, path: &std::path::Path) -> Result<std::path::PathBuf> {
+fn quarantine_broken(tx: &mut Tx, root: &std::path::Path, path: &std::path::Path) -> Result<std::path::PathBuf> {
     let broken_dir = root.join("broken");
-    move_file_avoiding_collision(path, &broken_dir)
+    move_file_avoiding_collision(tx, path, &broken_dir)
 }
 
-/// reopen用：active/ に戻し、さらに「新しいTS + slug」にリネーム
-pub fn move_to_active(cfg: &Config, todo: &TodoFile) -> Result<std::path::PathBuf> {
-    let active = cfg.active_dir();
-    std::fs::create_dir_all(&active)?;
+/// active/ 配下の新規ファイルパス（`YYYYMMDDhhmmss__slug.md`、衝突時は `_N` を付与）
+pub fn new_active_path(cfg: &Config, slug: Option<&str>) -> std::path::PathBuf {
+    new_path_in(&cfg.active_dir(), slug)
+}
 
+/// done/canceled を直接 done/YYYY/MM・canceled/YYYY/MM に作るときのパス（import 用。名前の付け方は add と同じ）
+/// ディレクトリは作らない
+pub fn new_archive_path(cfg: &Config, status: &Status, done_at: &str, slug: Option<&str>) -> Result<std::path::PathBuf> {
+    let base = match status {
+        Status::Done => cfg.done_dir(),
+        Status::Canceled => cfg.canceled_dir(),
+        _ => return Ok(new_active_path(cfg, slug)),
+    };
+    Ok(new_path_in(&month_dir(&base, done_at)?, slug))
+}
+
+/// "YYYYMMDDhhmmss__slug.md"（既にあれば _1, _2, ...）
+fn new_path_in(dir: &Path, slug: Option<&str>) -> std::path::PathBuf {
     let now_ts = Local::now().format("%Y%m%d%H%M%S").to_string();
-    let slug_s = {
-        let s = slug::slugify(&todo.fm.title);
-        if s.is_empty() { "todo".to_string() } else { s }
+
+    let base_name = match slug.filter(|s| !s.is_empty()) {
+        Some(s) => format!("{}__{}.md", now_ts, s),
+        None => format!("{}.md", now_ts),
     };
-    let base_name = format!("{}__{}.md", now_ts, slug_s);
 
-    let mut dest = active.join(base_name);
+    let mut dest = dir.join(base_name);
     if dest.exists() {
         let stem = dest.file_stem().and_then(|s| s.to_str()).unwrap_or("todo");
         let ext = dest.extension().and_then(|e| e.to_str()).unwrap_or("md");
         for i in 1..=9999 {
-            let cand = active.join(format!("{}_{}.{}", stem, i, ext));
+            let cand = dir.join(format!("{}_{}.{}", stem, i, ext));
             if !cand.exists() {
                 dest = cand;
                 break;
             }
         }
     }
+    dest
+}
+
+/// reopen用：active/ に戻し、さらに「新しいTS + slug」にリネーム
+pub fn move_to_active(cfg: &Config, tx: &mut Tx, todo: &TodoFile) -> Result<std::path::PathBuf> {
+    let active = cfg.active_dir();
+    std::fs::create_dir_all(&active)?;
+
+    let slug_s = {
+        let s = slug::slugify(&todo.fm.title);
+        if s.is_empty() { "todo".to_string() } else { s }
+    };
+    let dest = new_active_path(cfg, Some(&slug_s));
 
     if todo.path.starts_with(&active) {
         if todo.path != dest {
+            tx.note_rename(&todo.path, &dest);
             std::fs::rename(&todo.path, &dest)?;
         }
         return Ok(dest);
     }
 
+    tx.note_rename(&todo.path, &dest);
     std::fs::rename(&todo.path, &dest)?;
     Ok(dest)
 }
 
 /// archive等から active/ に戻す（ファイル名維持）＋ restored_from + 復旧ログ
-pub fn restore_to_active_preserve_name(cfg: &Config, todo: &TodoFile) -> Result<std::path::PathBuf> {
+pub fn restore_to_active_preserve_name(cfg: &Config, tx: &mut Tx, todo: &TodoFile) -> Result<std::path::PathBuf> {
     let active = cfg.active_dir();
     std::fs::create_dir_all(&active)?;
 
@@ -356,15 +592,15 @@ pub fn restore_to_active_preserve_name(cfg: &Config, todo: &TodoFile) -> Result<
     }
 
     let src_str = todo.path.display().to_string();
-    let dest = move_file_avoiding_collision(&todo.path, &active)?;
+    let dest = move_file_avoiding_collision(tx, &todo.path, &active)?;
 
     if let Ok(text) = std::fs::read_to_string(&dest) {
         if let Ok(mut tf) = parse_todo_file(dest.clone(), &text) {
-            let date = Local::now().format("%Y-%m-%d").to_string();
+            let at = activity::stamp();
             tf.fm.updated_at = now_jst_rfc3339();
             tf.fm.restored_from = Some(src_str);
-            tf.append_log_line(&date, "restored from archive");
-            save(&tf)?;
+            tf.append_log_line(&at, "restored from archive");
+            save(tx, &tf)?;
         }
     }
 
@@ -375,11 +611,12 @@ pub fn restore_to_active_preserve_name(cfg: &Config, todo: &TodoFile) -> Result<
 /// - active status => activeへ（ファイル名維持、restored_from/ログ追加）
 /// - done/canceled => done/canceled の YYYY/MM（dtは done_at -> updated_at -> created_at から推定）
 /// - 日付が取れない => unknown/
+///
 /// 移動後、restored_from を記録（移動元パス）
-pub fn place_todo_by_status(cfg: &Config, todo: &TodoFile) -> Result<std::path::PathBuf> {
+pub fn place_todo_by_status(cfg: &Config, tx: &mut Tx, todo: &TodoFile) -> Result<std::path::PathBuf> {
     match todo.fm.status {
         Status::Todo | Status::Doing | Status::Waiting => {
-            return restore_to_active_preserve_name(cfg, todo);
+            return restore_to_active_preserve_name(cfg, tx, todo);
         }
         Status::Done | Status::Canceled => {}
     }
@@ -410,13 +647,13 @@ pub fn place_todo_by_status(cfg: &Config, todo: &TodoFile) -> Result<std::path::
     };
 
     let src_str = todo.path.display().to_string();
-    let dest = move_file_avoiding_collision(&todo.path, &dest_dir)?;
+    let dest = move_file_avoiding_collision(tx, &todo.path, &dest_dir)?;
 
     if let Ok(text) = std::fs::read_to_string(&dest) {
         if let Ok(mut tf) = parse_todo_file(dest.clone(), &text) {
             tf.fm.updated_at = now_jst_rfc3339();
             tf.fm.restored_from = Some(src_str);
-            save(&tf)?;
+            save(tx, &tf)?;
         }
     }
 
@@ -427,14 +664,14 @@ pub fn place_todo_by_status(cfg: &Config, todo: &TodoFile) -> Result<std::path::
 /// - 壊れていれば broken/
 /// - active status なら active へ復旧（ログ+restored_from）
 /// - done/canceled は statusに従って YYYY/MM or unknown へ
-pub fn organize_archive(cfg: &Config) -> Result<usize> {
+pub fn organize_archive(cfg: &Config, tx: &mut Tx) -> Result<usize> {
     let mut moved = 0;
-    moved += organize_archive_root(cfg, &cfg.done_dir())?;
-    moved += organize_archive_root(cfg, &cfg.canceled_dir())?;
+    moved += organize_archive_root(cfg, tx, &cfg.done_dir())?;
+    moved += organize_archive_root(cfg, tx, &cfg.canceled_dir())?;
     Ok(moved)
 }
 
-fn organize_archive_root(cfg: &Config, root: &std::path::Path) -> Result<usize> {
+fn organize_archive_root(cfg: &Config, tx: &mut Tx, root: &std::path::Path) -> Result<usize> {
     if !root.exists() {
         return Ok(0);
     }
@@ -450,7 +687,7 @@ fn organize_archive_root(cfg: &Config, root: &std::path::Path) -> Result<usize>
         let text = match std::fs::read_to_string(&path) {
             Ok(s) => s,
             Err(_) => {
-                quarantine_broken(root, &path)?;
+                quarantine_broken(tx, root, &path)?;
                 moved += 1;
                 continue;
             }
@@ -459,13 +696,13 @@ fn organize_archive_root(cfg: &Config, root: &std::path::Path) -> Result<usize>
         let todo = match parse_todo_file(path.clone(), &text) {
             Ok(t) => t,
             Err(_) => {
-                quarantine_broken(root, &path)?;
+                quarantine_broken(tx, root, &path)?;
                 moved += 1;
                 continue;
             }
         };
 
-        place_todo_by_status(cfg, &todo)?;
+        place_todo_by_status(cfg, tx, &todo)?;
         moved += 1;
     }
 
@@ -473,7 +710,7 @@ fn organize_archive_root(cfg: &Config, root: &std::path::Path) -> Result<usize>
 }
 
 /// brokenファイル選択用（TodoFileにパースできないので Path だけ）
-pub fn select_broken_path_fzf(cfg: &Config) -> Result<Option<std::path::PathBuf>> {
+pub fn select_broken_path(cfg: &Config) -> Result<Option<std::path::PathBuf>> {
     let mut paths = Vec::new();
     let done_broken = cfg.done_dir().join("broken");
     let canceled_broken = cfg.canceled_dir().join("broken");
@@ -491,7 +728,14 @@ pub fn select_broken_path_fzf(cfg: &Config) -> Result<Option<std::path::PathBuf>
         return Ok(None);
     }
 
-    Ok(fzf_select_paths(&paths))
+    if use_fzf(cfg) {
+        return Ok(fzf_select_paths(&paths));
+    }
+    let items: Vec<Candidate> = paths
+        .iter()
+        .map(|p| Candidate { display: p.display().to_string(), path: Some(p.clone()) })
+        .collect();
+    Ok(picker::pick("broken> ", &items, &cfg.editor).map(|i| paths[i].clone()))
 }
 
 fn fzf_select_paths(paths: &[std::path::PathBuf]) -> Option<std::path::PathBuf> {
@@ -0,0 +1,217 @@
+use anyhow::{anyhow, Result};
+
+const HEADER: &str = "## サブタスク";
+
+/// `## サブタスク` 節のチェックリスト1項目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subtask {
+    pub text: String,
+    pub done: bool,
+    /// body.lines() 上の行番号
+    line: usize,
+}
+
+/// `- [ ] text` / `- [x] text` を (done, text) に分解
+fn parse_item(line: &str) -> Option<(bool, &str)> {
+    let t = line.trim_start();
+    let rest = t.strip_prefix("- [").or_else(|| t.strip_prefix("* ["))?;
+    let mut chars = rest.chars();
+    let mark = chars.next()?;
+    let rest = chars.as_str().strip_prefix(']')?;
+    let done = match mark {
+        ' ' => false,
+        'x' | 'X' => true,
+        _ => return None,
+    };
+    Some((done, rest.trim()))
+}
+
+/// サブタスク節の範囲（ヘッダ行の次から、次の `## ` 見出しの手前まで）
+fn section_range(lines: &[&str]) -> Option<(usize, usize)> {
+    let start = lines.iter().position(|l| l.trim_end() == HEADER)? + 1;
+    let end = lines[start..]
+        .iter()
+        .position(|l| l.starts_with("## "))
+        .map(|p| start + p)
+        .unwrap_or(lines.len());
+    Some((start, end))
+}
+
+/// 本文からサブタスクを読み出す（テンプレの空の `- [ ]` は数えない）
+pub fn parse(body: &str) -> Vec<Subtask> {
+    let lines: Vec<&str> = body.lines().collect();
+    let Some((start, end)) = section_range(&lines) else { return Vec::new(); };
+
+    let mut out = Vec::new();
+    for (i, line) in lines.iter().enumerate().take(end).skip(start) {
+        if let Some((done, text)) = parse_item(line) {
+            if text.is_empty() { continue; }
+            out.push(Subtask { text: text.to_string(), done, line: i });
+        }
+    }
+    out
+}
+
+/// (完了数, 全体数)
+pub fn progress(body: &str) -> (usize, usize) {
+    let items = parse(body);
+    (items.iter().filter(|s| s.done).count(), items.len())
+}
+
+/// 番号（1始まり）またはテキストで1件選ぶ
+/// テキストは完全一致 → 大文字小文字を無視した部分一致（一意な場合のみ）
+pub fn find<'a>(items: &'a [Subtask], key: &str) -> Result<&'a Subtask> {
+    let key = key.trim();
+    if let Ok(n) = key.parse::<usize>() {
+        return items
+            .get(n.wrapping_sub(1))
+            .ok_or_else(|| anyhow!("no subtask #{} (have {})", n, items.len()));
+    }
+    if let Some(s) = items.iter().find(|s| s.text == key) {
+        return Ok(s);
+    }
+    let lower = key.to_lowercase();
+    let hits: Vec<&Subtask> = items.iter().filter(|s| s.text.to_lowercase().contains(&lower)).collect();
+    match hits.len() {
+        0 => Err(anyhow!("no subtask matches: {}", key)),
+        1 => Ok(hits[0]),
+        _ => {
+            let mut msg = format!("multiple subtasks match '{}' (use a number):\n", key);
+            for s in hits {
+                let n = items.iter().position(|x| x.line == s.line).unwrap_or(0) + 1;
+                msg.push_str(&format!("  {}. {}\n", n, s.text));
+            }
+            Err(anyhow!(msg))
+        }
+    }
+}
+
+fn join_lines(lines: &[String], had_trailing_newline: bool) -> String {
+    let mut s = lines.join("\n");
+    if had_trailing_newline {
+        s.push('\n');
+    }
+    s
+}
+
+/// 末尾に `- [ ] text` を追加（節が無ければ `## ログ` の前か末尾に作る）
+pub fn add(body: &str, text: &str) -> String {
+    let trailing = body.ends_with('\n') || body.is_empty();
+    let mut lines: Vec<String> = body.lines().map(str::to_string).collect();
+    let item = format!("- [ ] {}", text.trim());
+
+    let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+    match section_range(&refs) {
+        Some((start, end)) => {
+            // テンプレの空項目があれば置き換える
+            if let Some(i) = (start..end).find(|&i| matches!(parse_item(&lines[i]), Some((_, "")))) {
+                lines[i] = item;
+                return join_lines(&lines, trailing);
+            }
+            // 最後の項目の直後（項目が無ければヘッダ直後）
+            let at = (start..end)
+                .rev()
+                .find(|&i| parse_item(&lines[i]).is_some())
+                .map(|i| i + 1)
+                .unwrap_or(start);
+            lines.insert(at, item);
+        }
+        None => {
+            let at = lines.iter().position(|l| l.trim_end() == "## ログ").unwrap_or(lines.len());
+            let mut block = vec![HEADER.to_string(), item, String::new()];
+            if at > 0 && !lines[at - 1].is_empty() {
+                block.insert(0, String::new());
+            }
+            lines.splice(at..at, block);
+        }
+    }
+    join_lines(&lines, true)
+}
+
+/// チェック状態を変更
+pub fn set_done(body: &str, target: &Subtask, done: bool) -> String {
+    let trailing = body.ends_with('\n');
+    let mut lines: Vec<String> = body.lines().map(str::to_string).collect();
+    if let Some(line) = lines.get_mut(target.line) {
+        let indent_len = line.len() - line.trim_start().len();
+        let (indent, rest) = line.split_at(indent_len);
+        let bullet = &rest[..1];
+        let mark = if done { "x" } else { " " };
+        *line = format!("{}{} [{}] {}", indent, bullet, mark, target.text);
+    }
+    join_lines(&lines, trailing)
+}
+
+/// 項目を削除
+pub fn remove(body: &str, target: &Subtask) -> String {
+    let trailing = body.ends_with('\n');
+    let mut lines: Vec<String> = body.lines().map(str::to_string).collect();
+    if target.line < lines.len() {
+        lines.remove(target.line);
+    }
+    join_lines(&lines, trailing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(body: &str) -> Vec<(bool, String)> {
+        parse(body).into_iter().map(|s| (s.done, s.text)).collect()
+    }
+
+    #[test]
+    fn add_replaces_the_empty_template_item() {
+        let body = "## サブタスク\n- [ ] \n\n## ログ\n- :\n";
+        assert_eq!(progress(body), (0, 0));
+        assert_eq!(add(body, " 見積もり "), "## サブタスク\n- [ ] 見積もり\n\n## ログ\n- :\n");
+    }
+
+    #[test]
+    fn add_appends_after_the_last_item() {
+        let body = "## サブタスク\n- [x] a\n- [ ] b\n\n## ログ\n";
+        assert_eq!(add(body, "c"), "## サブタスク\n- [x] a\n- [ ] b\n- [ ] c\n\n## ログ\n");
+        // 項目が無ければ見出しの直後
+        assert_eq!(add("## サブタスク\n\n## ログ\n", "c"), "## サブタスク\n- [ ] c\n\n## ログ\n");
+    }
+
+    #[test]
+    fn add_creates_a_missing_section() {
+        let body = "## メモ\nnote\n## ログ\n- 2026-01-01: created\n";
+        assert_eq!(add(body, "a"), "## メモ\nnote\n\n## サブタスク\n- [ ] a\n\n## ログ\n- 2026-01-01: created\n");
+        // ログ節も無ければ末尾
+        assert_eq!(add("", "a"), "## サブタスク\n- [ ] a\n\n");
+        assert_eq!(texts(&add("just text", "a")), [(false, "a".to_string())]);
+    }
+
+    #[test]
+    fn indented_and_star_items_keep_their_shape() {
+        let body = "## サブタスク\n  - [ ] a\n  * [X] b\n- [?] not an item\n## ログ\n- [ ] not a subtask\n";
+        assert_eq!(texts(body), [(false, "a".to_string()), (true, "b".to_string())]);
+
+        let items = parse(body);
+        let b = set_done(body, &items[1], false);
+        assert!(b.contains("\n  * [ ] b\n"), "{}", b);
+        let a = set_done(body, &items[0], true);
+        assert!(a.contains("\n  - [x] a\n"), "{}", a);
+        assert_eq!(texts(&remove(body, &items[0])), [(true, "b".to_string())]);
+        assert!(remove(body, &items[0]).ends_with("## ログ\n- [ ] not a subtask\n"));
+    }
+
+    #[test]
+    fn find_by_number_or_text() {
+        let items = parse("## サブタスク\n- [ ] Send invoice\n- [ ] Send reminder\n- [ ] Call\n");
+        assert_eq!(find(&items, "3").unwrap().text, "Call");
+        assert_eq!(find(&items, "invoice").unwrap().text, "Send invoice");
+        // 完全一致は部分一致より優先
+        let items2 = parse("## サブタスク\n- [ ] Call\n- [ ] Call back\n");
+        assert_eq!(find(&items2, "Call").unwrap().text, "Call");
+
+        assert!(find(&items, "0").unwrap_err().to_string().contains("no subtask #0"));
+        assert!(find(&items, "4").unwrap_err().to_string().contains("have 3"));
+        assert!(find(&items, "fax").unwrap_err().to_string().contains("no subtask matches"));
+        let e = find(&items, "send").unwrap_err().to_string();
+        assert!(e.contains("multiple subtasks match 'send'"), "{}", e);
+        assert!(e.contains("  1. Send invoice\n  2. Send reminder\n"), "{}", e);
+    }
+}
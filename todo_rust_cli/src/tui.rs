@@ -0,0 +1,499 @@
+use anyhow::{anyhow, Result};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Print,
+    terminal::{self, ClearType},
+};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crate::cli::ListFilter;
+use crate::config::Config;
+use crate::format::{self, Label};
+use crate::model::{Status, TodoFile};
+use crate::{filter, query, sort, store, subtask};
+
+const RELOAD_EVERY: Duration = Duration::from_secs(3);
+
+const HELP: &str = "j/k:move  s:start w:wait d:done c:cancel e:edit r:reopen  Tab:active/closed  /:filter  p:preview  q:quit";
+
+/// ラベルの表示順（グループ順）
+const GROUPS: &[Label] = &[Label::Overdue, Label::Today, Label::Soon, Label::Blocked, Label::None, Label::NoDue];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Active,
+    Closed,
+}
+
+enum Row {
+    Header(String),
+    Item(usize),
+}
+
+struct App<'a> {
+    cfg: &'a Config,
+    view: View,
+    filter: ListFilter,
+    filter_text: String,
+    /// Some のとき filter 入力中
+    input: Option<String>,
+
+    todos: Vec<TodoFile>,
+    labels: Vec<Label>,
+    rows: Vec<Row>,
+    /// rows 上の位置（Item の行を指す）
+    cursor: usize,
+    scroll: usize,
+
+    preview: bool,
+    message: String,
+    loaded_at: Instant,
+}
+
+/// raw mode / 代替画面を Drop で必ず戻す
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(std::io::stdout(), terminal::EnterAlternateScreen, terminal::DisableLineWrap, cursor::Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(std::io::stdout(), terminal::EnableLineWrap, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+pub fn run(cfg: &Config, filter: ListFilter) -> Result<()> {
+    let mut app = App {
+        cfg,
+        view: View::Active,
+        filter,
+        filter_text: String::new(),
+        input: None,
+        todos: Vec::new(),
+        labels: Vec::new(),
+        rows: Vec::new(),
+        cursor: 0,
+        scroll: 0,
+        preview: true,
+        message: String::new(),
+        loaded_at: Instant::now(),
+    };
+    app.filter_text = filter::describe(&app.filter);
+    app.reload()?;
+
+    let mut guard = Some(TerminalGuard::enter()?);
+
+    loop {
+        app.draw()?;
+
+        if !event::poll(Duration::from_millis(500))? {
+            if app.loaded_at.elapsed() >= RELOAD_EVERY && app.input.is_none() {
+                app.reload_keep_message();
+            }
+            continue;
+        }
+
+        let Event::Key(key) = event::read()? else { continue; };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        if app.input.is_some() {
+            app.on_input_key(key);
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char('j') | KeyCode::Down => app.move_by(1),
+            KeyCode::Char('k') | KeyCode::Up => app.move_by(-1),
+            KeyCode::PageDown => app.move_by(app.page() as isize),
+            KeyCode::PageUp => app.move_by(-(app.page() as isize)),
+            KeyCode::Char('g') | KeyCode::Home => app.move_to_edge(false),
+            KeyCode::Char('G') | KeyCode::End => app.move_to_edge(true),
+            KeyCode::Char('p') => app.preview = !app.preview,
+            KeyCode::Tab => {
+                app.view = if app.view == View::Active { View::Closed } else { View::Active };
+                app.cursor = 0;
+                app.scroll = 0;
+                app.reload_keep_message();
+            }
+            KeyCode::Char('/') => app.input = Some(app.filter_text.clone()),
+            KeyCode::Char('s') => app.act(|cfg, t| status_action(cfg, t, Status::Doing)),
+            KeyCode::Char('w') => app.act(|cfg, t| status_action(cfg, t, Status::Waiting)),
+            KeyCode::Char('d') => app.act(|cfg, t| status_action(cfg, t, Status::Done)),
+            KeyCode::Char('c') => app.act(|cfg, t| status_action(cfg, t, Status::Canceled)),
+            KeyCode::Char('r') => app.act(|cfg, t| {
+                let path = crate::reopen_todo(cfg, t)?;
+                Ok(format!("reopened: {}", path.display()))
+            }),
+            KeyCode::Char('e') => {
+                if let Some(t) = app.selected().cloned() {
+                    // エディタ中は端末を通常状態に戻す
+                    drop(guard.take());
+                    let res = crate::edit_in_editor(app.cfg, &t.path, &t.fm.title);
+                    guard = Some(TerminalGuard::enter()?);
+                    app.message = match res {
+                        Ok(()) => format!("updated: {}", t.path.display()),
+                        Err(e) => format!("error: {}", e),
+                    };
+                    app.reload_keep_message();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    drop(guard);
+    Ok(())
+}
+
+/// status 変更（start は blocker チェック付き）。cmd_set_status と同じ処理
+fn status_action(cfg: &Config, todo: TodoFile, status: Status) -> Result<String> {
+    if !todo.fm.status.is_active() {
+        return Err(anyhow!("not active: {} (use r to reopen)", todo.fm.status.as_str()));
+    }
+    if status == Status::Doing {
+        if let Some(msg) = crate::blocked_message(cfg, &todo)? {
+            return Err(anyhow!("{}", msg.lines().collect::<Vec<_>>().join(" / ")));
+        }
+    }
+    Ok(crate::apply_status(cfg, todo, status)?.join("  "))
+}
+
+impl App<'_> {
+    fn reload(&mut self) -> Result<()> {
+        let keep_id = self.selected().map(|t| t.fm.id.clone());
+
+        let active = store::load_active(self.cfg)?;
+        let mut todos = match self.view {
+            View::Active => {
+                let mut v = active.clone();
+                v.retain(|t| t.fm.status.is_active());
+                v
+            }
+            View::Closed => store::load_closed(self.cfg)?,
+        };
+        todos = filter::apply(self.cfg, todos, &self.filter)?;
+
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+        let mut ordered = Vec::new();
+
+        match self.view {
+            View::Active => {
+                let with_label: Vec<(TodoFile, Label)> = todos
+                    .into_iter()
+                    .map(|t| {
+                        let l = format::label_for_in(&t, &active, self.cfg.soon_days);
+                        (t, l)
+                    })
+                    .collect();
+                for g in GROUPS {
+                    let members: Vec<&(TodoFile, Label)> = with_label.iter().filter(|(_, l)| l == g).collect();
+                    if members.is_empty() {
+                        continue;
+                    }
+                    let name = if *g == Label::None { "LATER" } else { format::label_str(*g) };
+                    rows.push(Row::Header(format!("{} ({})", name, members.len())));
+                    for (t, l) in members {
+                        rows.push(Row::Item(ordered.len()));
+                        ordered.push(t.clone());
+                        labels.push(*l);
+                    }
+                }
+            }
+            View::Closed => {
+                if self.filter.sort.is_none() {
+                    todos.sort_by(|a, b| b.fm.done_at.cmp(&a.fm.done_at));
+                }
+                for st in [Status::Done, Status::Canceled] {
+                    let members: Vec<&TodoFile> = todos.iter().filter(|t| t.fm.status == st).collect();
+                    if members.is_empty() {
+                        continue;
+                    }
+                    rows.push(Row::Header(format!("{} ({})", st.as_str().to_uppercase(), members.len())));
+                    for t in members {
+                        rows.push(Row::Item(ordered.len()));
+                        ordered.push(t.clone());
+                        labels.push(Label::None);
+                    }
+                }
+            }
+        }
+
+        self.todos = ordered;
+        self.labels = labels;
+        self.rows = rows;
+        self.loaded_at = Instant::now();
+
+        let found = keep_id.and_then(|id| {
+            self.rows.iter().position(|r| matches!(r, Row::Item(i) if self.todos[*i].fm.id == id))
+        });
+        self.cursor = found.unwrap_or(self.cursor);
+        self.clamp_cursor();
+        Ok(())
+    }
+
+    fn reload_keep_message(&mut self) {
+        if let Err(e) = self.reload() {
+            self.message = format!("error: {}", e);
+        }
+    }
+
+    fn selected(&self) -> Option<&TodoFile> {
+        match self.rows.get(self.cursor)? {
+            Row::Item(i) => self.todos.get(*i),
+            Row::Header(_) => None,
+        }
+    }
+
+    fn is_item(&self, pos: usize) -> bool {
+        matches!(self.rows.get(pos), Some(Row::Item(_)))
+    }
+
+    fn clamp_cursor(&mut self) {
+        if self.rows.is_empty() {
+            self.cursor = 0;
+            return;
+        }
+        self.cursor = self.cursor.min(self.rows.len() - 1);
+        if !self.is_item(self.cursor) {
+            if let Some(p) = (self.cursor..self.rows.len()).find(|&p| self.is_item(p)) {
+                self.cursor = p;
+            } else if let Some(p) = (0..self.cursor).rev().find(|&p| self.is_item(p)) {
+                self.cursor = p;
+            }
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let items: Vec<usize> = (0..self.rows.len()).filter(|&p| self.is_item(p)).collect();
+        let Some(cur) = items.iter().position(|&p| p == self.cursor) else { return; };
+        let next = (cur as isize + delta).clamp(0, items.len() as isize - 1) as usize;
+        self.cursor = items[next];
+    }
+
+    fn move_to_edge(&mut self, end: bool) {
+        let mut items = (0..self.rows.len()).filter(|&p| self.is_item(p));
+        let target = if end { items.next_back() } else { items.next() };
+        if let Some(p) = target {
+            self.cursor = p;
+        }
+    }
+
+    fn page(&self) -> usize {
+        let (_, h) = terminal::size().unwrap_or((80, 24));
+        (h as usize).saturating_sub(3).max(1)
+    }
+
+    /// 選択中の todo に操作を適用して再読み込み
+    fn act(&mut self, f: impl FnOnce(&Config, TodoFile) -> Result<String>) {
+        let Some(t) = self.selected().cloned() else { return; };
+        let cfg = self.cfg;
+        // 表示中のデータは古いかもしれないので、lock を取ってから読み直す
+        let res = store::lock_root(cfg).and_then(|_lock| f(cfg, store::reload(&t)?));
+        self.message = match res {
+            Ok(m) => m,
+            Err(e) => format!("error: {}", e),
+        };
+        self.reload_keep_message();
+    }
+
+    fn on_input_key(&mut self, key: KeyEvent) {
+        let Some(buf) = self.input.as_mut() else { return; };
+        match key.code {
+            KeyCode::Esc => self.input = None,
+            KeyCode::Enter => {
+                let text = buf.clone();
+                self.input = None;
+                match parse_filter(&text) {
+                    Ok(f) => {
+                        self.filter = f;
+                        self.filter_text = text.trim().to_string();
+                        self.message.clear();
+                        self.cursor = 0;
+                        self.reload_keep_message();
+                    }
+                    // 1行に収める（--where のエラーは ^^^ 付きの複数行なので先頭行だけ）
+                    Err(e) => self.message = format!("error: {}", e.to_string().lines().next().unwrap_or("")),
+                }
+            }
+            KeyCode::Backspace => {
+                buf.pop();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => buf.clear(),
+            KeyCode::Char(c) => buf.push(c),
+            _ => {}
+        }
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let (w, h) = terminal::size()?;
+        let (w, h) = (w as usize, h as usize);
+        let body_h = h.saturating_sub(2);
+
+        let show_preview = self.preview && w >= 90;
+        let list_w = if show_preview { w * 11 / 20 } else { w };
+        let prev_w = w.saturating_sub(list_w + 3);
+
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if body_h > 0 && self.cursor >= self.scroll + body_h {
+            self.scroll = self.cursor + 1 - body_h;
+        }
+
+        let mut out = std::io::stdout().lock();
+
+        let view = if self.view == View::Active { "active" } else { "closed" };
+        let title = format!(" todo tui  [{}] {} item(s)  filter: {}", view, self.todos.len(), if self.filter_text.is_empty() { "-" } else { &self.filter_text });
+        queue!(out, cursor::MoveTo(0, 0), Print(format!("\x1b[7m{}\x1b[0m", format::pad(&title, w))), terminal::Clear(ClearType::UntilNewLine))?;
+
+        let preview_lines = match (show_preview, self.selected()) {
+            (true, Some(t)) => preview_lines(t),
+            _ => Vec::new(),
+        };
+
+        let color = std::env::var("NO_COLOR").is_err();
+        for y in 0..body_h {
+            let pos = self.scroll + y;
+            let mut line = match self.rows.get(pos) {
+                Some(Row::Header(s)) => {
+                    let text = format::pad(&format!("── {} ", s), list_w);
+                    if color { format!("\x1b[1m{}\x1b[0m", text) } else { text }
+                }
+                Some(Row::Item(i)) => {
+                    let t = &self.todos[*i];
+                    let text = format::pad(&self.item_line(t, self.labels[*i]), list_w);
+                    if pos == self.cursor { format!("\x1b[7m{}\x1b[0m", text) } else { text }
+                }
+                None => " ".repeat(list_w),
+            };
+            if show_preview {
+                line.push_str(" │ ");
+                if let Some(p) = preview_lines.get(y) {
+                    line.push_str(&format::truncate(p, prev_w));
+                }
+            }
+            queue!(out, cursor::MoveTo(0, (y + 1) as u16), Print(line), terminal::Clear(ClearType::UntilNewLine))?;
+        }
+
+        let status = match &self.input {
+            Some(buf) => format!("/{}", buf),
+            None if !self.message.is_empty() => self.message.clone(),
+            None => HELP.to_string(),
+        };
+        queue!(
+            out,
+            cursor::MoveTo(0, h.saturating_sub(1) as u16),
+            Print(format::truncate(&status, w.saturating_sub(1))),
+            terminal::Clear(ClearType::UntilNewLine)
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+
+    fn item_line(&self, t: &TodoFile, label: Label) -> String {
+        let (done, total) = subtask::progress(&t.body);
+        let sub = if total == 0 { String::new() } else { format!(" {}/{}", done, total) };
+        let tags = if t.fm.tags.is_empty() { String::new() } else { format!(" ({})", t.fm.tags.join(",")) };
+        let state = match (&self.view, &t.fm.status) {
+            (View::Active, Status::Doing) => "▶ ",
+            (View::Active, Status::Waiting) => "… ",
+            _ => "  ",
+        };
+        format!(
+            "{}{:<7} {:<10} [{}] {}{}{}",
+            state,
+            format::label_str(label),
+            format::due_display(t),
+            t.fm.importance,
+            t.fm.title,
+            sub,
+            tags
+        )
+    }
+}
+
+/// 右側プレビュー：主要フィールド + 本文
+fn preview_lines(t: &TodoFile) -> Vec<String> {
+    let mut v = vec![
+        t.fm.title.clone(),
+        format!(
+            "{} | due {} | importance {} | {}",
+            t.fm.status.as_str(),
+            t.fm.due.as_deref().unwrap_or("-"),
+            t.fm.importance,
+            t.fm.tags.join(",")
+        ),
+        t.path.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        String::new(),
+    ];
+    v.extend(t.body.lines().map(|l| l.replace('\t', "    ")));
+    v
+}
+
+/// filter 入力（`todo list` のフラグと同じ意味）
+/// - `tag:work` `status:doing` `imp:>=4` `within:14d` `overdue`
+/// - `sort:-importance,due`（グループ内の並び順）
+/// - `from:2026-01-01` `to:2026-01-31`
+/// - それ以外の語は text 検索
+/// - `?` 以降は --where の式（例：`tag:work ? imp>=4 or due<today`）
+fn parse_filter(s: &str) -> Result<ListFilter> {
+    let mut f = ListFilter::default();
+    let mut text = Vec::new();
+
+    let (s, expr) = match s.split_once('?') {
+        Some((head, expr)) => (head, Some(expr.trim())),
+        None => (s, None),
+    };
+    if let Some(expr) = expr.filter(|e| !e.is_empty()) {
+        query::parse(expr)?;
+        f.where_expr = Some(expr.to_string());
+    }
+
+    for tok in s.split_whitespace() {
+        let Some((k, v)) = tok.split_once(':') else {
+            if tok == "overdue" {
+                f.include_overdue = true;
+            } else {
+                text.push(tok);
+            }
+            continue;
+        };
+        match k {
+            "tag" => f.tag = Some(v.to_string()),
+            "status" => {
+                v.parse::<Status>()?;
+                f.status = Some(v.to_string());
+            }
+            "imp" | "importance" => f.importance = Some(v.to_string()),
+            "within" | "due" => {
+                filter::parse_days(v)?;
+                f.due_within = Some(v.to_string());
+            }
+            "from" => f.due_from = Some(v.to_string()),
+            "to" => f.due_to = Some(v.to_string()),
+            "sort" => {
+                sort::parse(v)?;
+                f.sort = Some(v.to_string());
+            }
+            "text" => text.push(v),
+            _ => return Err(anyhow!("unknown filter key: {} (tag/status/imp/within/from/to/sort/text/overdue)", k)),
+        }
+    }
+    if !text.is_empty() {
+        f.text = Some(text.join(" "));
+    }
+    Ok(f)
+}
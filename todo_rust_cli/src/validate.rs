@@ -0,0 +1,135 @@
+use anyhow::{anyhow, Result};
+use chrono::DateTime;
+
+use crate::model::{FrontMatter, Status, TodoFile};
+use crate::{recur, store};
+
+pub const IMPORTANCE_MIN: i32 = 1;
+pub const IMPORTANCE_MAX: i32 = 5;
+
+/// frontmatter の不正1件（どのフィールドか + 内容）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub field: &'static str,
+    pub message: String,
+}
+
+/// 1ファイル分の frontmatter を検査する（ファイル間の整合性は doctor 側）
+/// - id が空でない
+/// - importance が 1..5
+/// - due が YYYY-MM-DD / RFC3339
+/// - created_at / updated_at / done_at が RFC3339
+/// - done/canceled には done_at がある
+/// - repeat が解釈できる
+/// - clock の区間が RFC3339 で end >= start、計測中（end 無し）は1つまで
+pub fn check(fm: &FrontMatter) -> Vec<Issue> {
+    let mut out = Vec::new();
+    let mut push = |field: &'static str, message: String| out.push(Issue { field, message });
+
+    if fm.id.trim().is_empty() {
+        push("id", "empty id".to_string());
+    }
+
+    if !(IMPORTANCE_MIN..=IMPORTANCE_MAX).contains(&fm.importance) {
+        push(
+            "importance",
+            format!("{} is out of range ({}..{})", fm.importance, IMPORTANCE_MIN, IMPORTANCE_MAX),
+        );
+    }
+
+    if let Some(due) = fm.due.as_deref() {
+        if store::parse_due_dt(due).is_none() {
+            push("due", format!("unparsable date: {}", due));
+        }
+    }
+
+    for (field, value) in [("created_at", Some(&fm.created_at)), ("updated_at", Some(&fm.updated_at)), ("done_at", fm.done_at.as_ref())] {
+        if let Some(v) = value {
+            if DateTime::parse_from_rfc3339(v).is_err() {
+                push(field, format!("unparsable date: {}", v));
+            }
+        }
+    }
+
+    if matches!(fm.status, Status::Done | Status::Canceled) && fm.done_at.is_none() {
+        push("done_at", format!("missing for status {}", fm.status.as_str()));
+    }
+
+    if let Some(r) = fm.repeat.as_deref() {
+        if let Err(e) = r.parse::<recur::Repeat>() {
+            push("repeat", e.to_string());
+        }
+    }
+
+    for c in &fm.clock {
+        let start = DateTime::parse_from_rfc3339(&c.start);
+        let end = c.end.as_deref().map(DateTime::parse_from_rfc3339);
+        match (start, end) {
+            (Err(_), _) => push("clock", format!("unparsable start: {}", c.start)),
+            (_, Some(Err(_))) => push("clock", format!("unparsable end: {}", c.end.as_deref().unwrap_or(""))),
+            (Ok(s), Some(Ok(e))) if e < s => push("clock", format!("end {} is before start {}", e, s)),
+            _ => {}
+        }
+    }
+    if fm.clock.iter().filter(|c| c.end.is_none()).count() > 1 {
+        push("clock", "more than one running interval".to_string());
+    }
+
+    out
+}
+
+/// store::save 用：この操作で増えた不正があれば書き込まずにエラー
+/// - before は書き込み先にいま有るファイルの frontmatter（新規なら None）
+/// - 前から有った不正（手で書いた古い due など）は警告だけにして書く
+///   （そうしないと archive や status 変更がファイルを動かした後で止まり、エディタでの編集も保存できない）
+pub fn ensure_valid(todo: &TodoFile, before: Option<&FrontMatter>) -> Result<()> {
+    let existing = before.map(check).unwrap_or_default();
+    let (old, new): (Vec<Issue>, Vec<Issue>) = check(&todo.fm).into_iter().partition(|i| existing.contains(i));
+    for i in &old {
+        eprintln!("warning: {}: {}: {} (run: todo doctor)", todo.path.display(), i.field, i.message);
+    }
+    if new.is_empty() {
+        return Ok(());
+    }
+    let mut msg = format!("invalid frontmatter in {}:", todo.path.display());
+    for i in &new {
+        msg.push_str(&format!("\n  {}: {}", i.field, i.message));
+    }
+    msg.push_str("\n(fix the file by hand or run: todo doctor --fix)");
+    Err(anyhow!(msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter::parse_todo_file;
+
+    fn todo(due: &str) -> TodoFile {
+        let text = format!(
+            "---\nid: a\ntitle: t\nstatus: todo\ndue: {}\nimportance: 3\ncreated_at: 2026-01-01T09:00:00+09:00\nupdated_at: 2026-01-01T09:00:00+09:00\n---\n",
+            due
+        );
+        parse_todo_file(std::path::PathBuf::from("t.md"), &text).unwrap()
+    }
+
+    #[test]
+    fn new_problems_are_rejected() {
+        assert!(ensure_valid(&todo("2026-01-10"), None).is_ok());
+        assert!(ensure_valid(&todo("someday"), None).is_err());
+        assert!(ensure_valid(&todo("someday"), Some(&todo("2026-01-10").fm)).is_err());
+    }
+
+    #[test]
+    fn existing_problems_only_warn() {
+        let legacy = todo("someday");
+        let mut edited = legacy.clone();
+        edited.fm.status = Status::Done;
+        edited.fm.done_at = Some("2026-01-02T09:00:00+09:00".to_string());
+        assert!(ensure_valid(&edited, Some(&legacy.fm)).is_ok());
+
+        // 同じフィールドでも別の不正な値に変えたら拒否
+        assert!(ensure_valid(&todo("later"), Some(&legacy.fm)).is_err());
+        edited.fm.importance = 9;
+        assert!(ensure_valid(&edited, Some(&legacy.fm)).is_err());
+    }
+}
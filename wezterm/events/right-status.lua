@@ -25,6 +25,7 @@ local M = {}
 
 local ICON_SEPARATOR = nf.oct_dash
 local ICON_DATE = nf.fa_calendar
+local ICON_TODO = nf.md_format_list_checks
 
 ---@type string[]
 local discharging_icons = {
@@ -56,6 +57,7 @@ local charging_icons = {
 ---@type table<string, Cells.SegmentColors>
 -- stylua: ignore
 local colors = {
+   todo      = { fg = '#a6e3a1', bg = 'rgba(0, 0, 0, 0.4)' },
    date      = { fg = '#fab387', bg = 'rgba(0, 0, 0, 0.4)' },
    battery   = { fg = '#f9e2af', bg = 'rgba(0, 0, 0, 0.4)' },
    separator = { fg = '#74c7ec', bg = 'rgba(0, 0, 0, 0.4)' }
@@ -64,6 +66,8 @@ local colors = {
 local cells = Cells:new()
 
 cells
+   :add_segment('todo_icon', ICON_TODO .. '  ', colors.todo)
+   :add_segment('todo_text', '', colors.todo, attr(attr.intensity('Bold')))
    :add_segment('date_icon', ICON_DATE .. '  ', colors.date, attr(attr.intensity('Bold')))
    :add_segment('date_text', '', colors.date, attr(attr.intensity('Bold')))
    :add_segment('separator', ' ' .. ICON_SEPARATOR .. '  ', colors.separator)
@@ -99,19 +103,23 @@ M.setup = function(opts)
       wezterm.log_error(err)
    end
 
-   wezterm.on('update-right-status', function(window, _pane)
+   wezterm.on('update-right-status', function(window, pane)
       local battery_text, battery_icon = battery_info()
+      -- `todo status-line --user-var` をプロンプトごとに流しているペインなら todo の状況も出す
+      local todo_text = pane:get_user_vars().todo_status or ''
 
       cells
+         :update_segment_text('todo_text', todo_text)
          :update_segment_text('date_text', wezterm.strftime(valid_opts.date_format))
          :update_segment_text('battery_icon', battery_icon)
          :update_segment_text('battery_text', battery_text)
 
-      window:set_right_status(
-         wezterm.format(
-            cells:render({ 'date_icon', 'date_text', 'separator', 'battery_icon', 'battery_text' })
-         )
-      )
+      local ids = { 'date_icon', 'date_text', 'separator', 'battery_icon', 'battery_text' }
+      if todo_text ~= '' then
+         ids = { 'todo_icon', 'todo_text', 'separator', table.unpack(ids) }
+      end
+
+      window:set_right_status(wezterm.format(cells:render(ids)))
    end)
 end
 